@@ -0,0 +1,14 @@
+-- Create table for simplified ride tracks
+CREATE TABLE ride_tracks
+(
+    ride_id  INTEGER PRIMARY KEY NOT NULL REFERENCES rides (id) ON DELETE CASCADE,
+    -- simplified track in the encoded polyline format
+    polyline TEXT    NOT NULL,
+    source   TEXT    NOT NULL,
+    min_lat  DOUBLE  NOT NULL,
+    min_lon  DOUBLE  NOT NULL,
+    max_lat  DOUBLE  NOT NULL,
+    max_lon  DOUBLE  NOT NULL
+);
+
+CREATE INDEX ride_tracks_bounds ON ride_tracks (min_lat, max_lat, min_lon, max_lon);
@@ -0,0 +1,12 @@
+-- Index rides for date range queries
+CREATE INDEX rides_date ON rides (date);
+
+-- Create table for ride tags
+CREATE TABLE ride_tags
+(
+    ride_id INTEGER NOT NULL REFERENCES rides (id) ON DELETE CASCADE,
+    tag     TEXT    NOT NULL,
+    PRIMARY KEY (ride_id, tag)
+);
+
+CREATE INDEX ride_tags_tag ON ride_tags (tag);
@@ -0,0 +1,26 @@
+-- Full-text index over ride descriptions
+CREATE VIRTUAL TABLE rides_fts USING fts5
+(
+    description,
+    content = 'rides',
+    content_rowid = 'id'
+);
+
+INSERT INTO rides_fts (rides_fts) VALUES ('rebuild');
+
+-- Keep the index in sync with rides
+CREATE TRIGGER rides_fts_insert AFTER INSERT ON rides
+BEGIN
+    INSERT INTO rides_fts (rowid, description) VALUES (new.id, new.description);
+END;
+
+CREATE TRIGGER rides_fts_delete AFTER DELETE ON rides
+BEGIN
+    INSERT INTO rides_fts (rides_fts, rowid, description) VALUES ('delete', old.id, old.description);
+END;
+
+CREATE TRIGGER rides_fts_update AFTER UPDATE OF description ON rides
+BEGIN
+    INSERT INTO rides_fts (rides_fts, rowid, description) VALUES ('delete', old.id, old.description);
+    INSERT INTO rides_fts (rowid, description) VALUES (new.id, new.description);
+END;
@@ -0,0 +1,15 @@
+-- Create table for the history of changes to bikes and rides
+CREATE TABLE history
+(
+    id         INTEGER PRIMARY KEY AUTOINCREMENT,
+    entity     TEXT    NOT NULL,
+    entity_id  INTEGER NOT NULL,
+    action     TEXT    NOT NULL,
+    actor      TEXT    NOT NULL,
+    actor_id   INTEGER NULL,
+    before     TEXT    NULL,
+    after      TEXT    NULL,
+    created_at TEXT    NOT NULL
+);
+
+CREATE INDEX history_entity ON history (entity, entity_id);
@@ -0,0 +1,9 @@
+-- Create table for idempotency keys of created rides
+CREATE TABLE ride_idempotency_keys
+(
+    bike_id    INTEGER NOT NULL REFERENCES bikes (id) ON DELETE CASCADE,
+    key        TEXT    NOT NULL,
+    ride_id    INTEGER NOT NULL REFERENCES rides (id) ON DELETE CASCADE,
+    created_at TEXT    NOT NULL,
+    PRIMARY KEY (bike_id, key)
+);
@@ -0,0 +1,28 @@
+-- Track the last change of bikes and rides for incremental syncing
+ALTER TABLE bikes ADD COLUMN updated_at TEXT NOT NULL DEFAULT '1970-01-01 00:00:00.000';
+ALTER TABLE rides ADD COLUMN updated_at TEXT NOT NULL DEFAULT '1970-01-01 00:00:00.000';
+
+UPDATE bikes SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now');
+UPDATE rides SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now');
+
+CREATE INDEX rides_updated_at ON rides (updated_at);
+
+CREATE TRIGGER bikes_insert_updated_at AFTER INSERT ON bikes
+BEGIN
+    UPDATE bikes SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = new.id;
+END;
+
+CREATE TRIGGER bikes_update_updated_at AFTER UPDATE ON bikes WHEN new.updated_at IS old.updated_at
+BEGIN
+    UPDATE bikes SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = new.id;
+END;
+
+CREATE TRIGGER rides_insert_updated_at AFTER INSERT ON rides
+BEGIN
+    UPDATE rides SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = new.id;
+END;
+
+CREATE TRIGGER rides_update_updated_at AFTER UPDATE ON rides WHEN new.updated_at IS old.updated_at
+BEGIN
+    UPDATE rides SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = new.id;
+END;
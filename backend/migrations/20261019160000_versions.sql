@@ -0,0 +1,25 @@
+-- Version bikes and rides for optimistic concurrency control
+ALTER TABLE bikes ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE rides ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+
+-- Bump the version together with the update time, separate triggers would fire each other
+DROP TRIGGER bikes_update_updated_at;
+DROP TRIGGER rides_update_updated_at;
+
+CREATE TRIGGER bikes_update_version AFTER UPDATE ON bikes
+    WHEN new.version IS old.version AND new.updated_at IS old.updated_at
+BEGIN
+    UPDATE bikes
+    SET version    = old.version + 1,
+        updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now')
+    WHERE id = new.id;
+END;
+
+CREATE TRIGGER rides_update_version AFTER UPDATE ON rides
+    WHEN new.version IS old.version AND new.updated_at IS old.updated_at
+BEGIN
+    UPDATE rides
+    SET version    = old.version + 1,
+        updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now')
+    WHERE id = new.id;
+END;
@@ -0,0 +1,22 @@
+-- Create tables for bike components and their installations on bikes
+CREATE TABLE components
+(
+    id               INTEGER PRIMARY KEY AUTOINCREMENT,
+    owner_id         INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    kind             TEXT    NOT NULL,
+    brand            TEXT    NULL,
+    model            TEXT    NULL,
+    initial_distance REAL    NOT NULL DEFAULT 0,
+    created_at       TEXT    NOT NULL
+);
+
+CREATE TABLE component_installations
+(
+    id           INTEGER PRIMARY KEY AUTOINCREMENT,
+    component_id INTEGER NOT NULL REFERENCES components (id) ON DELETE CASCADE,
+    bike_id      INTEGER NOT NULL REFERENCES bikes (id) ON DELETE CASCADE,
+    installed_at TEXT    NOT NULL,
+    removed_at   TEXT    NULL
+);
+
+CREATE INDEX component_installations_component ON component_installations (component_id);
@@ -0,0 +1,20 @@
+-- Create tables for the maintenance log of bikes and the components touched by it
+CREATE TABLE maintenance
+(
+    id         INTEGER PRIMARY KEY AUTOINCREMENT,
+    bike_id    INTEGER NOT NULL REFERENCES bikes (id) ON DELETE CASCADE,
+    date       TEXT    NOT NULL,
+    kind       TEXT    NOT NULL,
+    cost       REAL    NULL,
+    notes      TEXT    NULL,
+    created_at TEXT    NOT NULL
+);
+
+CREATE INDEX maintenance_bike_date ON maintenance (bike_id, date);
+
+CREATE TABLE maintenance_components
+(
+    maintenance_id INTEGER NOT NULL REFERENCES maintenance (id) ON DELETE CASCADE,
+    component_id   INTEGER NOT NULL REFERENCES components (id) ON DELETE CASCADE,
+    PRIMARY KEY (maintenance_id, component_id)
+);
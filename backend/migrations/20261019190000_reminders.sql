@@ -0,0 +1,20 @@
+-- Add moving time to rides for time based reminders
+ALTER TABLE rides
+    ADD COLUMN moving_time INTEGER NULL;
+
+-- Create table for service reminders of bikes and components
+CREATE TABLE reminders
+(
+    id             INTEGER PRIMARY KEY AUTOINCREMENT,
+    owner_id       INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    bike_id        INTEGER NULL REFERENCES bikes (id) ON DELETE CASCADE,
+    component_id   INTEGER NULL REFERENCES components (id) ON DELETE CASCADE,
+    name           TEXT    NOT NULL,
+    every_distance REAL    NULL,
+    every_hours    REAL    NULL,
+    every_months   INTEGER NULL,
+    last_done_at   TEXT    NOT NULL,
+    due_since      TEXT    NULL,
+    created_at     TEXT    NOT NULL,
+    CHECK ((bike_id IS NULL) != (component_id IS NULL))
+);
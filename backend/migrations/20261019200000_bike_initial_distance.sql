@@ -0,0 +1,6 @@
+-- Add distance ridden before the bike was tracked
+ALTER TABLE bikes
+    ADD COLUMN initial_distance REAL NOT NULL DEFAULT 0;
+
+ALTER TABLE bikes
+    ADD COLUMN initial_distance_date TEXT NULL;
@@ -0,0 +1,24 @@
+-- Add specifications and purchase information to bikes
+ALTER TABLE bikes
+    ADD COLUMN bike_type TEXT NULL;
+
+ALTER TABLE bikes
+    ADD COLUMN frame_size TEXT NULL;
+
+ALTER TABLE bikes
+    ADD COLUMN wheel_size TEXT NULL;
+
+ALTER TABLE bikes
+    ADD COLUMN weight REAL NULL;
+
+ALTER TABLE bikes
+    ADD COLUMN model_year INTEGER NULL;
+
+ALTER TABLE bikes
+    ADD COLUMN purchase_date TEXT NULL;
+
+ALTER TABLE bikes
+    ADD COLUMN purchase_price REAL NULL;
+
+ALTER TABLE bikes
+    ADD COLUMN currency TEXT NULL;
@@ -0,0 +1,3 @@
+-- Identifier of the current photo of the bike in the media directory
+ALTER TABLE bikes
+    ADD COLUMN photo TEXT NULL;
@@ -0,0 +1,3 @@
+-- Retired bikes are hidden from selection, but their rides still count
+ALTER TABLE bikes
+    ADD COLUMN retired_at TEXT NULL;
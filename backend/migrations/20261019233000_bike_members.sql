@@ -0,0 +1,22 @@
+-- Share bikes between users, the owner of a bike is always one of its members
+CREATE TABLE bike_members
+(
+    bike_id    INTEGER NOT NULL REFERENCES bikes (id) ON DELETE CASCADE,
+    user_id    INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    role       TEXT    NOT NULL CHECK (role IN ('owner', 'rider', 'viewer')),
+    created_at TEXT    NOT NULL,
+    PRIMARY KEY (bike_id, user_id)
+);
+
+CREATE INDEX bike_members_user ON bike_members (user_id);
+
+INSERT INTO bike_members (bike_id, user_id, role, created_at)
+SELECT id, owner_id, 'owner', strftime('%Y-%m-%d %H:%M:%S', 'now')
+FROM bikes;
+
+-- Attribute rides to the member who rode them
+ALTER TABLE rides
+    ADD COLUMN rider_id INTEGER NULL REFERENCES users (id) ON DELETE SET NULL;
+
+UPDATE rides
+SET rider_id = (SELECT owner_id FROM bikes WHERE bikes.id = rides.bike_id);
@@ -0,0 +1,30 @@
+-- Hand over bikes to other users, the recipient has to accept the transfer
+CREATE TABLE bike_transfers
+(
+    id            INTEGER PRIMARY KEY AUTOINCREMENT,
+    bike_id       INTEGER NOT NULL REFERENCES bikes (id) ON DELETE CASCADE,
+    from_user_id  INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    to_user_id    INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    keep_snapshot BOOLEAN NOT NULL DEFAULT FALSE,
+    status        TEXT    NOT NULL CHECK (status IN ('pending', 'accepted', 'declined', 'cancelled')),
+    created_at    TEXT    NOT NULL,
+    resolved_at   TEXT    NULL
+);
+
+CREATE UNIQUE INDEX bike_transfers_pending ON bike_transfers (bike_id) WHERE status = 'pending';
+
+-- Totals of the previous owner on a transferred bike
+CREATE TABLE bike_snapshots
+(
+    id               INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id          INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    bike_id          INTEGER NULL REFERENCES bikes (id) ON DELETE SET NULL,
+    bike_name        TEXT    NOT NULL,
+    rides            INTEGER NOT NULL,
+    distance         REAL    NOT NULL,
+    moving_time      INTEGER NOT NULL,
+    maintenance_cost REAL    NOT NULL,
+    created_at       TEXT    NOT NULL
+);
+
+CREATE INDEX bike_snapshots_user ON bike_snapshots (user_id);
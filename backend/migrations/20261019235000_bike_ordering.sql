@@ -0,0 +1,11 @@
+-- Every member orders and groups the bikes in their own list
+ALTER TABLE bike_members
+    ADD COLUMN sort_order INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE bike_members
+    ADD COLUMN group_name TEXT NULL;
+
+UPDATE bike_members
+SET sort_order = (SELECT COUNT(*)
+                  FROM bike_members AS earlier
+                  WHERE earlier.user_id = bike_members.user_id
+                    AND earlier.bike_id < bike_members.bike_id);
@@ -0,0 +1,28 @@
+-- Remember when users gained or lost access to bikes, so syncing clients learn about both.
+-- Without foreign keys, changes outlive purged bikes and deleted users.
+CREATE TABLE bike_member_changes
+(
+    bike_id    INTEGER NOT NULL,
+    user_id    INTEGER NOT NULL,
+    removed    INTEGER NOT NULL,
+    changed_at TEXT    NOT NULL,
+    PRIMARY KEY (bike_id, user_id)
+);
+
+CREATE INDEX bike_member_changes_user ON bike_member_changes (user_id, changed_at);
+
+CREATE TRIGGER bike_members_insert_change AFTER INSERT ON bike_members
+BEGIN
+    INSERT INTO bike_member_changes (bike_id, user_id, removed, changed_at)
+    VALUES (new.bike_id, new.user_id, 0, strftime('%Y-%m-%d %H:%M:%f', 'now'))
+    ON CONFLICT (bike_id, user_id) DO UPDATE SET removed    = excluded.removed,
+                                                 changed_at = excluded.changed_at;
+END;
+
+CREATE TRIGGER bike_members_delete_change AFTER DELETE ON bike_members
+BEGIN
+    INSERT INTO bike_member_changes (bike_id, user_id, removed, changed_at)
+    VALUES (old.bike_id, old.user_id, 1, strftime('%Y-%m-%d %H:%M:%f', 'now'))
+    ON CONFLICT (bike_id, user_id) DO UPDATE SET removed    = excluded.removed,
+                                                 changed_at = excluded.changed_at;
+END;
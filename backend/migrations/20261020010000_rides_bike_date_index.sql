@@ -0,0 +1,3 @@
+-- Rides are listed per bike within a date range
+DROP INDEX rides_date;
+CREATE INDEX rides_bike_date ON rides (bike_id, date);
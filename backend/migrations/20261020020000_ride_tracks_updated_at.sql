@@ -0,0 +1,24 @@
+-- Track the last change of ride tracks and their tags for caching heatmap tiles
+ALTER TABLE ride_tracks ADD COLUMN updated_at TEXT NOT NULL DEFAULT '1970-01-01 00:00:00.000';
+
+UPDATE ride_tracks SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now');
+
+CREATE TRIGGER ride_tracks_insert_updated_at AFTER INSERT ON ride_tracks
+BEGIN
+    UPDATE ride_tracks SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE ride_id = new.ride_id;
+END;
+
+CREATE TRIGGER ride_tracks_update_updated_at AFTER UPDATE ON ride_tracks WHEN new.updated_at IS old.updated_at
+BEGIN
+    UPDATE ride_tracks SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE ride_id = new.ride_id;
+END;
+
+CREATE TRIGGER ride_tags_insert_track_updated_at AFTER INSERT ON ride_tags
+BEGIN
+    UPDATE ride_tracks SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE ride_id = new.ride_id;
+END;
+
+CREATE TRIGGER ride_tags_delete_track_updated_at AFTER DELETE ON ride_tags
+BEGIN
+    UPDATE ride_tracks SET updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE ride_id = old.ride_id;
+END;
@@ -2,10 +2,12 @@ use std::fmt::{Debug, Formatter};
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 
 use anyhow::Result;
-use axum::http::HeaderValue;
+use axum::http::{header, HeaderName, HeaderValue};
 use clap::Parser;
 use tower_http::cors::{Any, CorsLayer};
 
+use crate::services::bikes::rides::routes::{IDEMPOTENT_REPLAYED_HEADER, NEXT_CURSOR_HEADER};
+
 const IPV4_ALL: IpAddr = IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0));
 
 #[derive(Debug, Clone, Parser)]
@@ -43,6 +45,13 @@ pub struct Configuration {
         help = "Directory to serve static files from"
     )]
     pub static_dir: String,
+    #[arg(
+        long,
+        env = "BIKE_MEDIA_DIR",
+        default_value = "./media",
+        help = "Directory to store uploaded photos in"
+    )]
+    pub media_dir: String,
     #[arg(
         long,
         env = "BIKE_HOSTNAMES",
@@ -57,6 +66,13 @@ pub struct Configuration {
         help = "Maximum time in seconds for a session to be inactive before it is expired"
     )]
     pub session_max_inactivity: i64,
+    #[arg(
+        long,
+        env = "BIKE_TRASH_RETENTION_DAYS",
+        default_value_t = 30,
+        help = "Number of days deleted bikes and rides are kept in the trash before they are purged"
+    )]
+    pub trash_retention_days: i64,
     #[arg(
         long,
         env = "BIKE_STRAVA_CLIENT_ID",
@@ -93,7 +109,14 @@ impl Configuration {
             .iter()
             .map(|o| o.parse::<HeaderValue>())
             .collect::<Result<Vec<_>, _>>()?;
-        Ok(CorsLayer::new().allow_methods(Any).allow_origin(origins))
+        Ok(CorsLayer::new()
+            .allow_methods(Any)
+            .allow_origin(origins)
+            .expose_headers([
+                header::ETAG,
+                HeaderName::from_static(NEXT_CURSOR_HEADER),
+                HeaderName::from_static(IDEMPOTENT_REPLAYED_HEADER),
+            ]))
     }
 
     pub fn strava_config(&self) -> Option<StravaConfig> {
@@ -1,5 +1,6 @@
 use std::path::Path;
 
+use axum::extract::FromRef;
 use axum::Router;
 use clap::Parser;
 use dotenvy::dotenv;
@@ -9,6 +10,9 @@ use tower_http::services::{ServeDir, ServeFile};
 
 use crate::config::Configuration;
 use crate::services::api_router;
+use crate::services::bikes::photos::storage::PhotoStorage;
+use crate::services::trash::cleanup;
+use crate::services::trash::repository::TrashRepository;
 use crate::utility::state::AppState;
 
 mod config;
@@ -38,6 +42,12 @@ async fn main() -> anyhow::Result<()> {
         Path::new(&config.static_dir).join("index.html"),
     ));
     let state = AppState::new(config.clone(), pool);
+    tokio::spawn(cleanup::run(
+        TrashRepository::from_ref(&state),
+        PhotoStorage::from_ref(&state),
+        config.trash_retention_days,
+    ));
+
     let cors = config.create_cors_layer()?;
     let app = Router::new()
         .fallback_service(spa)
@@ -6,14 +6,23 @@ use axum::{
     response::IntoResponse,
     Router,
 };
+use reminders::middleware::evaluation_layer;
 
 use crate::utility::state::AppState;
 
 pub mod auth;
 pub mod bikes;
+pub mod components;
 pub mod data;
+pub mod heatmap;
+pub mod history;
+pub mod reminders;
+pub mod rides;
 pub mod status;
 pub mod strava;
+pub mod sync;
+pub mod transfers;
+pub mod trash;
 pub mod users;
 
 pub fn api_router(state: AppState) -> Router<AppState> {
@@ -28,19 +37,37 @@ pub fn api_router(state: AppState) -> Router<AppState> {
 }
 
 fn router_with_auth(state: AppState) -> Router<AppState> {
+    let evaluate_reminders = from_fn_with_state(state.clone(), evaluation_layer);
     Router::new()
-        .nest("/bikes", bikes::routes::router_with_auth())
+        .nest(
+            "/bikes",
+            bikes::routes::router_with_auth().route_layer(evaluate_reminders.clone()),
+        )
+        .nest("/components", components::routes::router_with_auth())
+        .nest("/heatmap", heatmap::routes::router_with_auth())
+        .nest("/reminders", reminders::routes::router_with_auth())
+        .nest(
+            "/rides",
+            rides::routes::router_with_auth().route_layer(evaluate_reminders.clone()),
+        )
         .nest("/users", users::routes::router_with_auth())
         .nest("/auth", auth::routes::router_with_auth())
-        .nest("/strava", strava::routes::router_with_auth())
+        .nest(
+            "/strava",
+            strava::routes::router_with_auth().route_layer(evaluate_reminders.clone()),
+        )
+        .nest("/sync", sync::routes::router_with_auth())
+        .nest("/transfers", transfers::routes::router_with_auth())
+        .nest("/trash", trash::routes::router_with_auth())
         .route_layer(from_fn_with_state(state, auth_layer))
 }
 
+/// Disables caching unless the response sets its own caching headers.
 async fn without_caching(request: Request, next: Next) -> impl IntoResponse {
     let mut response = next.run(request).await.into_response();
-    response.headers_mut().insert(
-        header::CACHE_CONTROL,
-        header::HeaderValue::from_static("no-store"),
-    );
+    response
+        .headers_mut()
+        .entry(header::CACHE_CONTROL)
+        .or_insert(header::HeaderValue::from_static("no-store"));
     response
 }
@@ -1,4 +1,7 @@
+pub mod maintenance;
+pub mod members;
 pub mod models;
+pub mod photos;
 pub mod repository;
 pub mod rides;
 pub mod routes;
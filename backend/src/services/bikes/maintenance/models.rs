@@ -0,0 +1,100 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::utility::{
+    db_extensions::Model,
+    db_format::{parse_date, parse_date_time},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MaintenanceKind {
+    ChainLube,
+    BrakeBleed,
+    FullService,
+    Other,
+}
+
+impl MaintenanceKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MaintenanceKind::ChainLube => "chainLube",
+            MaintenanceKind::BrakeBleed => "brakeBleed",
+            MaintenanceKind::FullService => "fullService",
+            MaintenanceKind::Other => "other",
+        }
+    }
+}
+
+impl FromStr for MaintenanceKind {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "chainLube" => Ok(MaintenanceKind::ChainLube),
+            "brakeBleed" => Ok(MaintenanceKind::BrakeBleed),
+            "fullService" => Ok(MaintenanceKind::FullService),
+            "other" => Ok(MaintenanceKind::Other),
+            _ => Err(anyhow!("Unknown maintenance kind {s}")),
+        }
+    }
+}
+
+/// Maintenance entry with the odometer reading of the bike on its date.
+#[derive(Debug, Clone)]
+pub struct MaintenanceRaw {
+    pub id: i64,
+    pub bike_id: i64,
+    pub date: String,
+    pub kind: String,
+    pub cost: Option<f64>,
+    pub notes: Option<String>,
+    pub created_at: String,
+    pub odometer: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceModel {
+    pub id: i64,
+    pub bike_id: i64,
+    pub date: NaiveDate,
+    pub kind: MaintenanceKind,
+    pub cost: Option<f64>,
+    pub notes: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub odometer: f64,
+    pub component_ids: Vec<i64>,
+}
+
+impl Model<MaintenanceRaw> for MaintenanceModel {}
+
+impl TryFrom<MaintenanceRaw> for MaintenanceModel {
+    type Error = anyhow::Error;
+    fn try_from(raw: MaintenanceRaw) -> Result<Self, Self::Error> {
+        Ok(MaintenanceModel {
+            id: raw.id,
+            bike_id: raw.bike_id,
+            date: parse_date(&raw.date)?,
+            kind: raw.kind.parse()?,
+            cost: raw.cost,
+            notes: raw.notes,
+            created_at: parse_date_time(&raw.created_at)?,
+            odometer: raw.odometer,
+            component_ids: Vec::new(),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenancePartial {
+    pub date: NaiveDate,
+    pub kind: MaintenanceKind,
+    pub cost: Option<f64>,
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub component_ids: Vec<i64>,
+}
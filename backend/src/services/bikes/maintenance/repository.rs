@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+
+use chrono::{NaiveDate, Utc};
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::utility::{
+    db_extensions::IntoModels,
+    db_format::{format_date, format_date_time},
+    error::{AppError, AppResult},
+};
+
+use super::models::{MaintenanceModel, MaintenancePartial, MaintenanceRaw};
+
+#[derive(Clone)]
+pub struct MaintenanceRepository(SqlitePool);
+
+impl MaintenanceRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self(pool)
+    }
+
+    pub async fn get_all_for_bike(&self, bike_id: i64) -> AppResult<Vec<MaintenanceModel>> {
+        // Odometer of the bike on the date of each entry
+        let models = sqlx::query_as!(
+            MaintenanceRaw,
+            r#"SELECT id AS "id!", bike_id AS "bike_id!", date AS "date!", kind AS "kind!", cost, notes, created_at AS "created_at!", (SELECT initial_distance FROM bikes WHERE bikes.id = maintenance.bike_id) + (SELECT COALESCE(SUM(rides.distance), 0.0) FROM rides WHERE rides.bike_id = maintenance.bike_id AND rides.date <= maintenance.date AND rides.deleted_at IS NULL) AS "odometer!: f64" FROM maintenance WHERE bike_id = ? ORDER BY date DESC, id DESC"#,
+            bike_id
+        )
+        .fetch_all(&self.0)
+        .await?
+        .into_models()?;
+
+        self.with_components(bike_id, models).await
+    }
+
+    pub async fn get_all_for_bike_between(
+        &self,
+        bike_id: i64,
+        from: &NaiveDate,
+        to: &NaiveDate,
+    ) -> AppResult<Vec<MaintenanceModel>> {
+        let from = format_date(from);
+        let to = format_date(to);
+        let models = sqlx::query_as!(
+            MaintenanceRaw,
+            r#"SELECT id AS "id!", bike_id AS "bike_id!", date AS "date!", kind AS "kind!", cost, notes, created_at AS "created_at!", (SELECT initial_distance FROM bikes WHERE bikes.id = maintenance.bike_id) + (SELECT COALESCE(SUM(rides.distance), 0.0) FROM rides WHERE rides.bike_id = maintenance.bike_id AND rides.date <= maintenance.date AND rides.deleted_at IS NULL) AS "odometer!: f64" FROM maintenance WHERE bike_id = ? AND date >= ? AND date <= ? ORDER BY date DESC, id DESC"#,
+            bike_id,
+            from,
+            to
+        )
+        .fetch_all(&self.0)
+        .await?
+        .into_models()?;
+
+        self.with_components(bike_id, models).await
+    }
+
+    pub async fn get_one_for_bike(
+        &self,
+        bike_id: i64,
+        maintenance_id: i64,
+    ) -> AppResult<MaintenanceModel> {
+        let model = sqlx::query_as!(
+            MaintenanceRaw,
+            r#"SELECT id AS "id!", bike_id AS "bike_id!", date AS "date!", kind AS "kind!", cost, notes, created_at AS "created_at!", (SELECT initial_distance FROM bikes WHERE bikes.id = maintenance.bike_id) + (SELECT COALESCE(SUM(rides.distance), 0.0) FROM rides WHERE rides.bike_id = maintenance.bike_id AND rides.date <= maintenance.date AND rides.deleted_at IS NULL) AS "odometer!: f64" FROM maintenance WHERE id = ? AND bike_id = ?"#,
+            maintenance_id,
+            bike_id
+        )
+        .fetch_optional(&self.0)
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(format!("No maintenance found with id {maintenance_id}"))
+        })?
+        .try_into()?;
+
+        let mut models = self.with_components(bike_id, vec![model]).await?;
+        Ok(models.remove(0))
+    }
+
+    pub async fn create(
+        &self,
+        bike_id: i64,
+        maintenance: &MaintenancePartial,
+    ) -> AppResult<MaintenanceModel> {
+        let date = format_date(&maintenance.date);
+        let kind = maintenance.kind.as_str();
+        let created_at = format_date_time(&Utc::now().naive_utc());
+
+        let mut tx = self.0.begin().await?;
+        let id = sqlx::query!(
+            "INSERT INTO maintenance (bike_id, date, kind, cost, notes, created_at) VALUES (?, ?, ?, ?, ?, ?)",
+            bike_id,
+            date,
+            kind,
+            maintenance.cost,
+            maintenance.notes,
+            created_at
+        )
+        .execute(&mut *tx)
+        .await?
+        .last_insert_rowid();
+        Self::set_components_in(&mut tx, id, &maintenance.component_ids).await?;
+        tx.commit().await?;
+
+        self.get_one_for_bike(bike_id, id).await
+    }
+
+    pub async fn update(
+        &self,
+        bike_id: i64,
+        maintenance_id: i64,
+        maintenance: &MaintenancePartial,
+    ) -> AppResult<MaintenanceModel> {
+        let date = format_date(&maintenance.date);
+        let kind = maintenance.kind.as_str();
+
+        let mut tx = self.0.begin().await?;
+        sqlx::query!(
+            "UPDATE maintenance SET date = ?, kind = ?, cost = ?, notes = ? WHERE id = ?",
+            date,
+            kind,
+            maintenance.cost,
+            maintenance.notes,
+            maintenance_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        Self::set_components_in(&mut tx, maintenance_id, &maintenance.component_ids).await?;
+        tx.commit().await?;
+
+        self.get_one_for_bike(bike_id, maintenance_id).await
+    }
+
+    pub async fn delete(&self, maintenance_id: i64) -> AppResult<()> {
+        sqlx::query!("DELETE FROM maintenance WHERE id = ?", maintenance_id)
+            .execute(&self.0)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_cost_for_bike(&self, bike_id: i64) -> AppResult<f64> {
+        let cost = sqlx::query_scalar!(
+            r#"SELECT COALESCE(SUM(cost), 0.0) AS "cost!: f64" FROM maintenance WHERE bike_id = ?"#,
+            bike_id
+        )
+        .fetch_one(&self.0)
+        .await?;
+
+        Ok(cost)
+    }
+
+    pub async fn get_costs(&self, user_id: i64) -> AppResult<HashMap<i64, f64>> {
+        let rows = sqlx::query!(
+            r#"SELECT maintenance.bike_id, COALESCE(SUM(maintenance.cost), 0.0) AS "cost!: f64" FROM maintenance JOIN bike_members ON bike_members.bike_id = maintenance.bike_id WHERE bike_members.user_id = ? GROUP BY maintenance.bike_id"#,
+            user_id
+        )
+        .fetch_all(&self.0)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.bike_id, row.cost))
+            .collect())
+    }
+
+    async fn set_components_in(
+        conn: &mut SqliteConnection,
+        maintenance_id: i64,
+        component_ids: &[i64],
+    ) -> AppResult<()> {
+        sqlx::query!(
+            "DELETE FROM maintenance_components WHERE maintenance_id = ?",
+            maintenance_id
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        for component_id in component_ids {
+            sqlx::query!(
+                "INSERT OR IGNORE INTO maintenance_components (maintenance_id, component_id) VALUES (?, ?)",
+                maintenance_id,
+                component_id
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn with_components(
+        &self,
+        bike_id: i64,
+        mut models: Vec<MaintenanceModel>,
+    ) -> AppResult<Vec<MaintenanceModel>> {
+        let rows = sqlx::query!(
+            "SELECT maintenance_components.maintenance_id, maintenance_components.component_id FROM maintenance_components JOIN maintenance ON maintenance.id = maintenance_components.maintenance_id WHERE maintenance.bike_id = ? ORDER BY maintenance_components.component_id",
+            bike_id
+        )
+        .fetch_all(&self.0)
+        .await?;
+
+        let mut components = HashMap::<i64, Vec<i64>>::new();
+        for row in rows {
+            components
+                .entry(row.maintenance_id)
+                .or_default()
+                .push(row.component_id);
+        }
+
+        for model in &mut models {
+            model.component_ids = components.remove(&model.id).unwrap_or_default();
+        }
+
+        Ok(models)
+    }
+}
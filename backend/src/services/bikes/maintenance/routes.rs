@@ -0,0 +1,117 @@
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::routing::{delete, get, post, put};
+use axum::{Extension, Json, Router};
+
+use crate::services::auth::models::SessionModel;
+use crate::services::bikes::members::models::BikeRole;
+use crate::services::bikes::repository::BikeRepository;
+use crate::services::components::repository::ComponentRepository;
+use crate::utility::error::AppResult;
+use crate::utility::state::AppState;
+
+use super::models::{MaintenanceModel, MaintenancePartial};
+use super::repository::MaintenanceRepository;
+
+pub fn router_with_auth() -> Router<AppState> {
+    Router::new()
+        .route("/", get(get_all_maintenance))
+        .route("/", post(create_maintenance))
+        .route("/{id}", get(get_maintenance))
+        .route("/{id}", put(update_maintenance))
+        .route("/{id}", delete(delete_maintenance))
+}
+
+async fn get_all_maintenance(
+    State(bike_repo): State<BikeRepository>,
+    State(maintenance_repo): State<MaintenanceRepository>,
+    Path(bike_id): Path<i64>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<Json<Vec<MaintenanceModel>>> {
+    bike_repo
+        .assert_role(bike_id, session.user_id, BikeRole::Viewer)
+        .await?;
+
+    let models = maintenance_repo.get_all_for_bike(bike_id).await?;
+    Ok(Json(models))
+}
+
+async fn create_maintenance(
+    State(bike_repo): State<BikeRepository>,
+    State(component_repo): State<ComponentRepository>,
+    State(maintenance_repo): State<MaintenanceRepository>,
+    Path(bike_id): Path<i64>,
+    Extension(session): Extension<SessionModel>,
+    Json(payload): Json<MaintenancePartial>,
+) -> AppResult<(StatusCode, Json<MaintenanceModel>)> {
+    bike_repo
+        .assert_role(bike_id, session.user_id, BikeRole::Rider)
+        .await?;
+    for component_id in &payload.component_ids {
+        component_repo
+            .assert_usable_on_bike(*component_id, bike_id)
+            .await?;
+    }
+
+    let model = maintenance_repo.create(bike_id, &payload).await?;
+    Ok((StatusCode::CREATED, Json(model)))
+}
+
+async fn get_maintenance(
+    State(bike_repo): State<BikeRepository>,
+    State(maintenance_repo): State<MaintenanceRepository>,
+    Path((bike_id, maintenance_id)): Path<(i64, i64)>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<Json<MaintenanceModel>> {
+    bike_repo
+        .assert_role(bike_id, session.user_id, BikeRole::Viewer)
+        .await?;
+
+    let model = maintenance_repo
+        .get_one_for_bike(bike_id, maintenance_id)
+        .await?;
+    Ok(Json(model))
+}
+
+async fn update_maintenance(
+    State(bike_repo): State<BikeRepository>,
+    State(component_repo): State<ComponentRepository>,
+    State(maintenance_repo): State<MaintenanceRepository>,
+    Path((bike_id, maintenance_id)): Path<(i64, i64)>,
+    Extension(session): Extension<SessionModel>,
+    Json(payload): Json<MaintenancePartial>,
+) -> AppResult<Json<MaintenanceModel>> {
+    bike_repo
+        .assert_role(bike_id, session.user_id, BikeRole::Rider)
+        .await?;
+    for component_id in &payload.component_ids {
+        component_repo
+            .assert_usable_on_bike(*component_id, bike_id)
+            .await?;
+    }
+
+    maintenance_repo
+        .get_one_for_bike(bike_id, maintenance_id)
+        .await?;
+    let model = maintenance_repo
+        .update(bike_id, maintenance_id, &payload)
+        .await?;
+    Ok(Json(model))
+}
+
+async fn delete_maintenance(
+    State(bike_repo): State<BikeRepository>,
+    State(maintenance_repo): State<MaintenanceRepository>,
+    Path((bike_id, maintenance_id)): Path<(i64, i64)>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<StatusCode> {
+    bike_repo
+        .assert_role(bike_id, session.user_id, BikeRole::Rider)
+        .await?;
+
+    maintenance_repo
+        .get_one_for_bike(bike_id, maintenance_id)
+        .await?;
+    maintenance_repo.delete(maintenance_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,142 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::services::bikes::rides::models::RideModel;
+use crate::utility::{
+    db_extensions::Model,
+    db_format::parse_date_time,
+    error::{AppError, AppResult},
+};
+
+/// Role of a user on a shared bike, ordered by the permissions it grants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BikeRole {
+    /// Sees the bike and its rides
+    Viewer,
+    /// Also logs rides and maintenance, edits only own rides
+    Rider,
+    /// Manages the bike, its members and all of its rides
+    Owner,
+}
+
+impl BikeRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BikeRole::Viewer => "viewer",
+            BikeRole::Rider => "rider",
+            BikeRole::Owner => "owner",
+        }
+    }
+
+    /// Owners edit all rides of the bike, riders only the rides they rode.
+    pub fn assert_can_edit_ride(&self, ride: &RideModel, user_id: i64) -> AppResult<()> {
+        let allowed = match self {
+            BikeRole::Owner => true,
+            BikeRole::Rider => ride.rider_id == Some(user_id),
+            BikeRole::Viewer => false,
+        };
+
+        match allowed {
+            true => Ok(()),
+            false => Err(AppError::Forbidden),
+        }
+    }
+}
+
+impl FromStr for BikeRole {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "viewer" => Ok(BikeRole::Viewer),
+            "rider" => Ok(BikeRole::Rider),
+            "owner" => Ok(BikeRole::Owner),
+            _ => Err(anyhow!("Unknown bike role {s}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BikeMemberRaw {
+    pub bike_id: i64,
+    pub user_id: i64,
+    pub username: String,
+    pub role: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BikeMemberModel {
+    pub bike_id: i64,
+    pub user_id: i64,
+    pub username: String,
+    pub role: BikeRole,
+    pub created_at: NaiveDateTime,
+}
+
+impl Model<BikeMemberRaw> for BikeMemberModel {}
+
+impl TryFrom<BikeMemberRaw> for BikeMemberModel {
+    type Error = anyhow::Error;
+    fn try_from(raw: BikeMemberRaw) -> Result<Self, Self::Error> {
+        Ok(BikeMemberModel {
+            bike_id: raw.bike_id,
+            user_id: raw.user_id,
+            username: raw.username,
+            role: raw.role.parse()?,
+            created_at: parse_date_time(&raw.created_at)?,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BikeMemberPartial {
+    pub username: String,
+    pub role: BikeRole,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BikeMemberRole {
+    pub role: BikeRole,
+}
+
+/// Role of the user on the bike and its place in their list of bikes.
+#[derive(Debug, Clone)]
+pub struct BikeMembership {
+    pub role: BikeRole,
+    pub sort_order: i64,
+    pub group: Option<String>,
+}
+
+/// User gaining or losing access to a bike.
+#[derive(Debug, Clone)]
+pub struct AccessChange {
+    pub bike_id: i64,
+    pub removed: bool,
+    pub changed_at: NaiveDateTime,
+}
+
+/// Entry of the new order of bikes, the position in the list is the sort order.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BikeOrder {
+    pub bike_id: i64,
+    /// Road, MTB, commuter, ...
+    #[serde(default)]
+    pub group: Option<String>,
+}
+
+/// Ride totals of a single rider on the bike, rides of deleted users have no rider.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RiderTotals {
+    pub rider_id: Option<i64>,
+    pub username: Option<String>,
+    pub rides: i64,
+    pub distance: f64,
+    pub moving_time: i64,
+}
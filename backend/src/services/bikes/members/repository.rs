@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+
+use chrono::{NaiveDateTime, Utc};
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::utility::{
+    db_extensions::IntoModels,
+    db_format::{format_date_time, format_timestamp, parse_timestamp},
+    error::{AppError, AppResult},
+};
+
+use super::models::{
+    AccessChange, BikeMemberModel, BikeMemberRaw, BikeMembership, BikeOrder, BikeRole, RiderTotals,
+};
+
+#[derive(Clone)]
+pub struct MemberRepository(SqlitePool);
+
+impl MemberRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self(pool)
+    }
+
+    pub async fn get_all(&self, bike_id: i64) -> AppResult<Vec<BikeMemberModel>> {
+        let models = sqlx::query_as!(
+            BikeMemberRaw,
+            r#"SELECT bike_members.bike_id AS "bike_id!", bike_members.user_id AS "user_id!", users.username AS "username!", bike_members.role AS "role!", bike_members.created_at AS "created_at!" FROM bike_members JOIN users ON users.id = bike_members.user_id WHERE bike_members.bike_id = ? ORDER BY bike_members.created_at, bike_members.user_id"#,
+            bike_id
+        )
+        .fetch_all(&self.0)
+        .await?
+        .into_models()?;
+
+        Ok(models)
+    }
+
+    pub async fn get_one(&self, bike_id: i64, user_id: i64) -> AppResult<BikeMemberModel> {
+        let model = sqlx::query_as!(
+            BikeMemberRaw,
+            r#"SELECT bike_members.bike_id AS "bike_id!", bike_members.user_id AS "user_id!", users.username AS "username!", bike_members.role AS "role!", bike_members.created_at AS "created_at!" FROM bike_members JOIN users ON users.id = bike_members.user_id WHERE bike_members.bike_id = ? AND bike_members.user_id = ?"#,
+            bike_id,
+            user_id
+        )
+        .fetch_optional(&self.0)
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(format!("User {user_id} is not a member of bike {bike_id}"))
+        })?
+        .try_into()?;
+
+        Ok(model)
+    }
+
+    /// Memberships of the user per bike.
+    pub async fn get_memberships(&self, user_id: i64) -> AppResult<HashMap<i64, BikeMembership>> {
+        let rows = sqlx::query!(
+            "SELECT bike_id, role, sort_order, group_name FROM bike_members WHERE user_id = ?",
+            user_id
+        )
+        .fetch_all(&self.0)
+        .await?;
+
+        let mut memberships = HashMap::with_capacity(rows.len());
+        for row in rows {
+            let membership = BikeMembership {
+                role: row.role.parse()?,
+                sort_order: row.sort_order,
+                group: row.group_name,
+            };
+            memberships.insert(row.bike_id, membership);
+        }
+        Ok(memberships)
+    }
+
+    /// Bikes the user was added to or removed from since the given time.
+    pub async fn get_access_changes(
+        &self,
+        user_id: i64,
+        since: &NaiveDateTime,
+    ) -> AppResult<Vec<AccessChange>> {
+        let since = format_timestamp(since);
+        let rows = sqlx::query!(
+            "SELECT bike_id, removed, changed_at FROM bike_member_changes WHERE user_id = ? AND changed_at >= ? ORDER BY changed_at, bike_id",
+            user_id,
+            since
+        )
+        .fetch_all(&self.0)
+        .await?;
+
+        let mut changes = Vec::with_capacity(rows.len());
+        for row in rows {
+            changes.push(AccessChange {
+                bike_id: row.bike_id,
+                removed: row.removed != 0,
+                changed_at: parse_timestamp(&row.changed_at).map_err(anyhow::Error::from)?,
+            });
+        }
+        Ok(changes)
+    }
+
+    /// Moves the given bikes to the top of the list of the user in the given order,
+    /// bikes left out keep their relative order below them.
+    pub async fn reorder(&self, user_id: i64, order: &[BikeOrder]) -> AppResult<()> {
+        let shift = order.len() as i64;
+        let mut tx = self.0.begin().await?;
+        sqlx::query!(
+            "UPDATE bike_members SET sort_order = sort_order + ? WHERE user_id = ?",
+            shift,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        for (sort_order, entry) in order.iter().enumerate() {
+            let sort_order = sort_order as i64;
+            let group = entry
+                .group
+                .as_deref()
+                .map(str::trim)
+                .filter(|group| !group.is_empty());
+            let result = sqlx::query!(
+                "UPDATE bike_members SET sort_order = ?, group_name = ? WHERE bike_id = ? AND user_id = ?",
+                sort_order,
+                group,
+                entry.bike_id,
+                user_id
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            if result.rows_affected() == 0 {
+                return Err(AppError::Forbidden);
+            }
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Adds the user to the bike or changes their role.
+    pub async fn set(
+        &self,
+        bike_id: i64,
+        user_id: i64,
+        role: BikeRole,
+    ) -> AppResult<BikeMemberModel> {
+        Self::set_in(&mut *self.0.acquire().await?, bike_id, user_id, role).await?;
+        self.get_one(bike_id, user_id).await
+    }
+
+    pub async fn remove(&self, bike_id: i64, user_id: i64) -> AppResult<()> {
+        sqlx::query!(
+            "DELETE FROM bike_members WHERE bike_id = ? AND user_id = ?",
+            bike_id,
+            user_id
+        )
+        .execute(&self.0)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_rider_totals(&self, bike_id: i64) -> AppResult<Vec<RiderTotals>> {
+        let totals = sqlx::query_as!(
+            RiderTotals,
+            r#"SELECT rides.rider_id, users.username AS "username?", COUNT(*) AS "rides!: i64", COALESCE(SUM(rides.distance), 0.0) AS "distance!: f64", COALESCE(SUM(rides.moving_time), 0) AS "moving_time!: i64" FROM rides LEFT JOIN users ON users.id = rides.rider_id WHERE rides.bike_id = ? AND rides.deleted_at IS NULL GROUP BY rides.rider_id ORDER BY 4 DESC"#,
+            bike_id
+        )
+        .fetch_all(&self.0)
+        .await?;
+
+        Ok(totals)
+    }
+
+    pub async fn set_in(
+        conn: &mut SqliteConnection,
+        bike_id: i64,
+        user_id: i64,
+        role: BikeRole,
+    ) -> AppResult<()> {
+        let role = role.as_str();
+        let now = format_date_time(&Utc::now().naive_utc());
+        sqlx::query!(
+            "INSERT INTO bike_members (bike_id, user_id, role, created_at, sort_order) VALUES (?, ?, ?, ?, (SELECT COALESCE(MAX(sort_order) + 1, 0) FROM bike_members WHERE user_id = ?)) ON CONFLICT (bike_id, user_id) DO UPDATE SET role = excluded.role",
+            bike_id,
+            user_id,
+            role,
+            now,
+            user_id
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+}
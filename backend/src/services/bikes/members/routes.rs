@@ -0,0 +1,115 @@
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::routing::{delete, get, post, put};
+use axum::{Extension, Json, Router};
+
+use crate::services::auth::models::SessionModel;
+use crate::services::bikes::repository::BikeRepository;
+use crate::services::users::repository::UserRepository;
+use crate::utility::error::{AppError, AppResult};
+use crate::utility::state::AppState;
+
+use super::models::{BikeMemberModel, BikeMemberPartial, BikeMemberRole, BikeRole};
+use super::repository::MemberRepository;
+
+pub fn router_with_auth() -> Router<AppState> {
+    Router::new()
+        .route("/", get(get_members))
+        .route("/", post(add_member))
+        .route("/{user_id}", put(update_member))
+        .route("/{user_id}", delete(remove_member))
+}
+
+async fn get_members(
+    State(bike_repo): State<BikeRepository>,
+    State(member_repo): State<MemberRepository>,
+    Path(bike_id): Path<i64>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<Json<Vec<BikeMemberModel>>> {
+    bike_repo
+        .assert_role(bike_id, session.user_id, BikeRole::Viewer)
+        .await?;
+
+    let models = member_repo.get_all(bike_id).await?;
+    Ok(Json(models))
+}
+
+async fn add_member(
+    State(bike_repo): State<BikeRepository>,
+    State(member_repo): State<MemberRepository>,
+    State(user_repo): State<UserRepository>,
+    Path(bike_id): Path<i64>,
+    Extension(session): Extension<SessionModel>,
+    Json(payload): Json<BikeMemberPartial>,
+) -> AppResult<(StatusCode, Json<BikeMemberModel>)> {
+    bike_repo
+        .assert_role(bike_id, session.user_id, BikeRole::Owner)
+        .await?;
+
+    let user = user_repo
+        .try_get_by_username(&payload.username)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("No user {}", payload.username)))?;
+    if member_repo.get_one(bike_id, user.id).await.is_ok() {
+        return Err(AppError::Conflict(format!(
+            "User {} is already a member of the bike",
+            payload.username
+        )));
+    }
+
+    let model = member_repo.set(bike_id, user.id, payload.role).await?;
+    Ok((StatusCode::CREATED, Json(model)))
+}
+
+async fn update_member(
+    State(bike_repo): State<BikeRepository>,
+    State(member_repo): State<MemberRepository>,
+    Path((bike_id, user_id)): Path<(i64, i64)>,
+    Extension(session): Extension<SessionModel>,
+    Json(payload): Json<BikeMemberRole>,
+) -> AppResult<Json<BikeMemberModel>> {
+    bike_repo
+        .assert_role(bike_id, session.user_id, BikeRole::Owner)
+        .await?;
+    assert_not_bike_owner(&bike_repo, bike_id, user_id).await?;
+
+    member_repo.get_one(bike_id, user_id).await?;
+    let model = member_repo.set(bike_id, user_id, payload.role).await?;
+    Ok(Json(model))
+}
+
+/// Owners remove any member, other members can only leave the bike.
+async fn remove_member(
+    State(bike_repo): State<BikeRepository>,
+    State(member_repo): State<MemberRepository>,
+    Path((bike_id, user_id)): Path<(i64, i64)>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<StatusCode> {
+    let required = match user_id == session.user_id {
+        true => BikeRole::Viewer,
+        false => BikeRole::Owner,
+    };
+    bike_repo
+        .assert_role(bike_id, session.user_id, required)
+        .await?;
+    assert_not_bike_owner(&bike_repo, bike_id, user_id).await?;
+
+    member_repo.get_one(bike_id, user_id).await?;
+    member_repo.remove(bike_id, user_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn assert_not_bike_owner(
+    bike_repo: &BikeRepository,
+    bike_id: i64,
+    user_id: i64,
+) -> AppResult<()> {
+    let bike = bike_repo.get_one(bike_id).await?;
+    if bike.owner_id == user_id {
+        return Err(AppError::Conflict(String::from(
+            "Role of the bike owner cannot be changed",
+        )));
+    }
+
+    Ok(())
+}
@@ -1,9 +1,20 @@
-use chrono::NaiveDateTime;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use chrono::{NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 
+use crate::services::bikes::maintenance::models::MaintenanceModel;
+use crate::services::bikes::members::models::BikeRole;
+use crate::services::bikes::rides::models::RideModel;
+
 use crate::utility::{
     db_extensions::Model,
-    db_format::{format_date_time, parse_date_time},
+    db_format::{
+        format_date, format_date_time, format_timestamp, parse_date, parse_date_time,
+        parse_timestamp,
+    },
+    patch::present,
 };
 
 #[derive(Debug, Clone)]
@@ -15,6 +26,62 @@ pub struct BikeRaw {
     pub color: Option<String>,
     pub owner_id: i64,
     pub strava_gear: Option<String>,
+    pub updated_at: String,
+    pub version: i64,
+    pub initial_distance: f64,
+    pub initial_distance_date: Option<String>,
+    pub bike_type: Option<String>,
+    pub frame_size: Option<String>,
+    pub wheel_size: Option<String>,
+    pub weight: Option<f64>,
+    pub model_year: Option<i64>,
+    pub purchase_date: Option<String>,
+    pub purchase_price: Option<f64>,
+    pub currency: Option<String>,
+    pub photo: Option<String>,
+    pub retired_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BikeType {
+    Road,
+    Gravel,
+    Mountain,
+    City,
+    Touring,
+    Electric,
+    Other,
+}
+
+impl BikeType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BikeType::Road => "road",
+            BikeType::Gravel => "gravel",
+            BikeType::Mountain => "mountain",
+            BikeType::City => "city",
+            BikeType::Touring => "touring",
+            BikeType::Electric => "electric",
+            BikeType::Other => "other",
+        }
+    }
+}
+
+impl FromStr for BikeType {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "road" => Ok(BikeType::Road),
+            "gravel" => Ok(BikeType::Gravel),
+            "mountain" => Ok(BikeType::Mountain),
+            "city" => Ok(BikeType::City),
+            "touring" => Ok(BikeType::Touring),
+            "electric" => Ok(BikeType::Electric),
+            "other" => Ok(BikeType::Other),
+            _ => Err(anyhow!("Unknown bike type {s}")),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,6 +94,37 @@ pub struct BikeModel {
     pub color: Option<String>,
     pub owner_id: i64,
     pub strava_gear: Option<String>,
+    #[serde(default)]
+    pub updated_at: NaiveDateTime,
+    #[serde(default)]
+    pub version: i64,
+    /// Distance ridden before the bike was tracked
+    #[serde(default)]
+    pub initial_distance: f64,
+    #[serde(default)]
+    pub initial_distance_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub bike_type: Option<BikeType>,
+    #[serde(default)]
+    pub frame_size: Option<String>,
+    #[serde(default)]
+    pub wheel_size: Option<String>,
+    /// Weight in kilograms
+    #[serde(default)]
+    pub weight: Option<f64>,
+    #[serde(default)]
+    pub model_year: Option<i64>,
+    #[serde(default)]
+    pub purchase_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub purchase_price: Option<f64>,
+    #[serde(default)]
+    pub currency: Option<String>,
+    /// Identifier of the current photo, changes with every upload
+    #[serde(default)]
+    pub photo: Option<String>,
+    #[serde(default)]
+    pub retired_at: Option<NaiveDateTime>,
 }
 
 impl Model<BikeRaw> for BikeModel {}
@@ -43,6 +141,23 @@ impl TryFrom<BikeRaw> for BikeModel {
             color: raw.color,
             owner_id: raw.owner_id,
             strava_gear: raw.strava_gear,
+            updated_at: parse_timestamp(&raw.updated_at)?,
+            version: raw.version,
+            initial_distance: raw.initial_distance,
+            initial_distance_date: raw
+                .initial_distance_date
+                .map(|s| parse_date(&s))
+                .transpose()?,
+            bike_type: raw.bike_type.map(|s| s.parse()).transpose()?,
+            frame_size: raw.frame_size,
+            wheel_size: raw.wheel_size,
+            weight: raw.weight,
+            model_year: raw.model_year,
+            purchase_date: raw.purchase_date.map(|s| parse_date(&s)).transpose()?,
+            purchase_price: raw.purchase_price,
+            currency: raw.currency,
+            photo: raw.photo,
+            retired_at: raw.retired_at.map(|s| parse_date_time(&s)).transpose()?,
         })
     }
 }
@@ -58,15 +173,196 @@ impl From<BikeModel> for BikeRaw {
             color: model.color,
             owner_id: model.owner_id,
             strava_gear: model.strava_gear,
+            updated_at: format_timestamp(&model.updated_at),
+            version: model.version,
+            initial_distance: model.initial_distance,
+            initial_distance_date: model.initial_distance_date.map(|d| format_date(&d)),
+            bike_type: model.bike_type.map(|t| t.as_str().to_string()),
+            frame_size: model.frame_size,
+            wheel_size: model.wheel_size,
+            weight: model.weight,
+            model_year: model.model_year,
+            purchase_date: model.purchase_date.map(|d| format_date(&d)),
+            purchase_price: model.purchase_price,
+            currency: model.currency,
+            photo: model.photo,
+            retired_at: model.retired_at.map(|d| format_date_time(&d)),
         }
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BikePartial {
     pub name: String,
     pub description: Option<String>,
     pub color: Option<String>,
     pub strava_gear: Option<String>,
+    #[serde(default)]
+    pub initial_distance: f64,
+    #[serde(default)]
+    pub initial_distance_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub bike_type: Option<BikeType>,
+    #[serde(default)]
+    pub frame_size: Option<String>,
+    #[serde(default)]
+    pub wheel_size: Option<String>,
+    #[serde(default)]
+    pub weight: Option<f64>,
+    #[serde(default)]
+    pub model_year: Option<i64>,
+    #[serde(default)]
+    pub purchase_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub purchase_price: Option<f64>,
+    #[serde(default)]
+    pub currency: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BikePatch {
+    #[serde(default, deserialize_with = "present")]
+    pub name: Option<String>,
+    #[serde(default, deserialize_with = "present")]
+    pub description: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub color: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub strava_gear: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub initial_distance: Option<f64>,
+    #[serde(default, deserialize_with = "present")]
+    pub initial_distance_date: Option<Option<NaiveDate>>,
+    #[serde(default, deserialize_with = "present")]
+    pub bike_type: Option<Option<BikeType>>,
+    #[serde(default, deserialize_with = "present")]
+    pub frame_size: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub wheel_size: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub weight: Option<Option<f64>>,
+    #[serde(default, deserialize_with = "present")]
+    pub model_year: Option<Option<i64>>,
+    #[serde(default, deserialize_with = "present")]
+    pub purchase_date: Option<Option<NaiveDate>>,
+    #[serde(default, deserialize_with = "present")]
+    pub purchase_price: Option<Option<f64>>,
+    #[serde(default, deserialize_with = "present")]
+    pub currency: Option<Option<String>>,
+}
+
+impl BikePatch {
+    pub fn apply(self, bike: &BikeModel) -> BikePartial {
+        BikePartial {
+            name: self.name.unwrap_or_else(|| bike.name.clone()),
+            description: self.description.unwrap_or_else(|| bike.description.clone()),
+            color: self.color.unwrap_or_else(|| bike.color.clone()),
+            strava_gear: self.strava_gear.unwrap_or_else(|| bike.strava_gear.clone()),
+            initial_distance: self.initial_distance.unwrap_or(bike.initial_distance),
+            initial_distance_date: self
+                .initial_distance_date
+                .unwrap_or(bike.initial_distance_date),
+            bike_type: self.bike_type.unwrap_or(bike.bike_type),
+            frame_size: self.frame_size.unwrap_or_else(|| bike.frame_size.clone()),
+            wheel_size: self.wheel_size.unwrap_or_else(|| bike.wheel_size.clone()),
+            weight: self.weight.unwrap_or(bike.weight),
+            model_year: self.model_year.unwrap_or(bike.model_year),
+            purchase_date: self.purchase_date.unwrap_or(bike.purchase_date),
+            purchase_price: self.purchase_price.unwrap_or(bike.purchase_price),
+            currency: self.currency.unwrap_or_else(|| bike.currency.clone()),
+        }
+    }
+}
+
+/// Lifetime distance of the bike including the distance before it was tracked
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Odometer {
+    pub initial_distance: f64,
+    pub rides_distance: f64,
+    pub distance: f64,
+}
+
+impl Odometer {
+    pub fn new(initial_distance: f64, rides_distance: f64) -> Self {
+        Odometer {
+            initial_distance,
+            rides_distance,
+            distance: initial_distance + rides_distance,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BikeWithOdometer {
+    #[serde(flatten)]
+    pub bike: BikeModel,
+    pub odometer: Odometer,
+    /// Role of the requesting user on the bike
+    pub role: BikeRole,
+    /// Position of the bike in the list of the requesting user
+    pub sort_order: i64,
+    pub group: Option<String>,
+}
+
+/// Cost of owning the bike per kilometer of its lifetime distance,
+/// maintenance costs are assumed to be in the currency of the bike.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BikeCosts {
+    pub bike_id: i64,
+    pub currency: Option<String>,
+    pub purchase_price: f64,
+    pub maintenance_cost: f64,
+    pub total_cost: f64,
+    pub distance: f64,
+    pub cost_per_km: Option<f64>,
+}
+
+impl BikeCosts {
+    pub fn new(bike: &BikeModel, maintenance_cost: f64, odometer: &Odometer) -> Self {
+        let purchase_price = bike.purchase_price.unwrap_or_default();
+        let total_cost = purchase_price + maintenance_cost;
+        BikeCosts {
+            bike_id: bike.id,
+            currency: bike.currency.clone(),
+            purchase_price,
+            maintenance_cost,
+            total_cost,
+            distance: odometer.distance,
+            cost_per_km: (odometer.distance > 0.0).then(|| total_cost / odometer.distance),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BikeListQuery {
+    /// Include retired bikes
+    #[serde(default)]
+    pub retired: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TimelineRange {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum TimelineEvent {
+    Ride(RideModel),
+    Maintenance(MaintenanceModel),
+}
+
+impl TimelineEvent {
+    pub fn date(&self) -> NaiveDate {
+        match self {
+            TimelineEvent::Ride(ride) => ride.date,
+            TimelineEvent::Maintenance(maintenance) => maintenance.date,
+        }
+    }
 }
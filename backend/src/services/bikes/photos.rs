@@ -0,0 +1,3 @@
+pub mod processing;
+pub mod routes;
+pub mod storage;
@@ -0,0 +1,69 @@
+use std::io::Cursor;
+
+use anyhow::{anyhow, Result};
+use image::codecs::jpeg::JpegEncoder;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader, Limits};
+
+pub const ACCEPTED_CONTENT_TYPES: [&str; 3] = ["image/jpeg", "image/png", "image/webp"];
+
+const PHOTO_SIZE: u32 = 2048;
+const THUMBNAIL_SIZE: u32 = 320;
+const JPEG_QUALITY: u8 = 85;
+const MAX_DIMENSION: u32 = 12_000;
+const MAX_ALLOCATION: u64 = 512 * 1024 * 1024;
+
+pub struct ProcessedPhoto {
+    pub photo: Vec<u8>,
+    pub thumbnail: Vec<u8>,
+}
+
+/// Decodes the uploaded image and re-encodes it as a downscaled photo and a thumbnail.
+///
+/// Only the pixels are kept, so EXIF and other metadata are stripped,
+/// the EXIF orientation is applied beforehand.
+pub fn process_photo(data: &[u8], content_type: &str) -> Result<ProcessedPhoto> {
+    let declared = ImageFormat::from_mime_type(content_type)
+        .filter(|_| ACCEPTED_CONTENT_TYPES.contains(&content_type))
+        .ok_or_else(|| anyhow!("Unsupported content type {content_type}"))?;
+
+    let mut reader = ImageReader::new(Cursor::new(data)).with_guessed_format()?;
+    if reader.format() != Some(declared) {
+        return Err(anyhow!(
+            "Content does not match content type {content_type}"
+        ));
+    }
+
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_DIMENSION);
+    limits.max_image_height = Some(MAX_DIMENSION);
+    limits.max_alloc = Some(MAX_ALLOCATION);
+    reader.limits(limits);
+
+    let mut decoder = reader.into_decoder()?;
+    let orientation = decoder.orientation()?;
+    let mut image = DynamicImage::from_decoder(decoder)?;
+    image.apply_orientation(orientation);
+
+    let photo = if image.width() > PHOTO_SIZE || image.height() > PHOTO_SIZE {
+        image.resize(
+            PHOTO_SIZE,
+            PHOTO_SIZE,
+            image::imageops::FilterType::Lanczos3,
+        )
+    } else {
+        image
+    };
+    let thumbnail = photo.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE);
+
+    Ok(ProcessedPhoto {
+        photo: encode_jpeg(&photo)?,
+        thumbnail: encode_jpeg(&thumbnail)?,
+    })
+}
+
+fn encode_jpeg(image: &DynamicImage) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    let encoder = JpegEncoder::new_with_quality(&mut data, JPEG_QUALITY);
+    image.to_rgb8().write_with_encoder(encoder)?;
+    Ok(data)
+}
@@ -0,0 +1,210 @@
+use axum::extract::{DefaultBodyLimit, Multipart, Path, Query, State};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get, put};
+use axum::{Extension, Json, Router};
+use serde::Deserialize;
+
+use crate::services::auth::models::SessionModel;
+use crate::services::bikes::members::models::BikeRole;
+use crate::services::bikes::models::BikeModel;
+use crate::services::bikes::repository::BikeRepository;
+use crate::utility::error::{AppError, AppResult};
+use crate::utility::state::AppState;
+use crate::utility::versioning::if_none_match;
+
+use super::processing::{process_photo, ACCEPTED_CONTENT_TYPES};
+use super::storage::{PhotoSize, PhotoStorage};
+
+const PHOTO_FILE_LIMIT: usize = 16 * 1024 * 1024;
+
+pub fn router_with_auth() -> Router<AppState> {
+    Router::new()
+        .route("/", get(get_photo))
+        .route(
+            "/",
+            put(upload_photo).layer(DefaultBodyLimit::max(PHOTO_FILE_LIMIT)),
+        )
+        .route("/", delete(delete_photo))
+        .route("/thumbnail", get(get_thumbnail))
+}
+
+#[derive(Debug, Deserialize)]
+struct PhotoQuery {
+    /// Photo identifier the client got from the bike, makes the response immutable
+    v: Option<String>,
+}
+
+async fn get_photo(
+    State(bike_repo): State<BikeRepository>,
+    State(storage): State<PhotoStorage>,
+    Path(bike_id): Path<i64>,
+    Query(query): Query<PhotoQuery>,
+    Extension(session): Extension<SessionModel>,
+    headers: HeaderMap,
+) -> AppResult<Response> {
+    serve(
+        &bike_repo,
+        &storage,
+        bike_id,
+        &session,
+        &query,
+        &headers,
+        PhotoSize::Full,
+    )
+    .await
+}
+
+async fn get_thumbnail(
+    State(bike_repo): State<BikeRepository>,
+    State(storage): State<PhotoStorage>,
+    Path(bike_id): Path<i64>,
+    Query(query): Query<PhotoQuery>,
+    Extension(session): Extension<SessionModel>,
+    headers: HeaderMap,
+) -> AppResult<Response> {
+    serve(
+        &bike_repo,
+        &storage,
+        bike_id,
+        &session,
+        &query,
+        &headers,
+        PhotoSize::Thumbnail,
+    )
+    .await
+}
+
+async fn upload_photo(
+    State(bike_repo): State<BikeRepository>,
+    State(storage): State<PhotoStorage>,
+    Path(bike_id): Path<i64>,
+    Extension(session): Extension<SessionModel>,
+    mut multipart: Multipart,
+) -> AppResult<Json<BikeModel>> {
+    bike_repo
+        .assert_role(bike_id, session.user_id, BikeRole::Owner)
+        .await?;
+
+    let mut upload = None;
+    while let Some(field) = multipart.next_field().await? {
+        if let Some("photo") = field.name() {
+            let content_type = field.content_type().unwrap_or_default().to_string();
+            if !ACCEPTED_CONTENT_TYPES.contains(&content_type.as_str()) {
+                return Err(AppError::BadRequest(format!(
+                    "Unsupported photo type, expected one of {}",
+                    ACCEPTED_CONTENT_TYPES.join(", ")
+                )));
+            }
+            upload = Some((field.bytes().await?, content_type));
+            break;
+        }
+    }
+
+    let (data, content_type) =
+        upload.ok_or_else(|| AppError::BadRequest(String::from("No photo provided")))?;
+    let processed = tokio::task::spawn_blocking(move || process_photo(&data, &content_type))
+        .await
+        .map_err(anyhow::Error::from)?
+        .map_err(|e| AppError::BadRequest(format!("Invalid photo: {e}")))?;
+
+    let before = bike_repo.get_one(bike_id).await?;
+    let photo_id = storage.save(&processed).await?;
+    let swapped = bike_repo
+        .swap_photo(bike_id, before.photo.as_deref(), Some(&photo_id))
+        .await;
+    match swapped {
+        Ok(Some(model)) => {
+            if let Some(previous) = before.photo {
+                storage.delete(&previous).await?;
+            }
+            Ok(Json(model))
+        }
+        // Another upload replaced the photo meanwhile, the new file is never referenced
+        Ok(None) => {
+            storage.delete(&photo_id).await?;
+            Err(AppError::Conflict(format!(
+                "Photo of bike {bike_id} was changed meanwhile"
+            )))
+        }
+        Err(e) => {
+            storage.delete(&photo_id).await?;
+            Err(e)
+        }
+    }
+}
+
+async fn delete_photo(
+    State(bike_repo): State<BikeRepository>,
+    State(storage): State<PhotoStorage>,
+    Path(bike_id): Path<i64>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<StatusCode> {
+    bike_repo
+        .assert_role(bike_id, session.user_id, BikeRole::Owner)
+        .await?;
+
+    let before = bike_repo.get_one(bike_id).await?;
+    if let Some(previous) = before.photo {
+        // The file is left to whoever replaced the photo meanwhile
+        if bike_repo
+            .swap_photo(bike_id, Some(&previous), None)
+            .await?
+            .is_some()
+        {
+            storage.delete(&previous).await?;
+        }
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Serves the photo with an `ETag` of its identifier, answering `If-None-Match` with 304.
+///
+/// Requests for a specific photo identifier can be cached forever,
+/// otherwise the client has to revalidate as the photo may get replaced.
+async fn serve(
+    bike_repo: &BikeRepository,
+    storage: &PhotoStorage,
+    bike_id: i64,
+    session: &SessionModel,
+    query: &PhotoQuery,
+    headers: &HeaderMap,
+    size: PhotoSize,
+) -> AppResult<Response> {
+    bike_repo
+        .assert_role(bike_id, session.user_id, BikeRole::Viewer)
+        .await?;
+
+    let bike = bike_repo.get_one(bike_id).await?;
+    let photo_id = bike
+        .photo
+        .ok_or_else(|| AppError::NotFound(format!("Bike {bike_id} has no photo")))?;
+
+    let etag = match size {
+        PhotoSize::Full => format!("\"{photo_id}\""),
+        PhotoSize::Thumbnail => format!("\"{photo_id}-thumbnail\""),
+    };
+    let cache_control = if query.v.as_deref() == Some(photo_id.as_str()) {
+        "private, max-age=31536000, immutable"
+    } else {
+        "private, no-cache"
+    };
+
+    let mut response_headers = HeaderMap::new();
+    response_headers.insert(
+        header::ETAG,
+        HeaderValue::from_str(&etag).map_err(anyhow::Error::from)?,
+    );
+    response_headers.insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static(cache_control),
+    );
+
+    if if_none_match(headers, &etag) {
+        return Ok((StatusCode::NOT_MODIFIED, response_headers).into_response());
+    }
+
+    let data = storage.read(&photo_id, size).await?;
+    response_headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("image/jpeg"));
+    Ok((response_headers, data).into_response())
+}
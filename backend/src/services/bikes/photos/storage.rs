@@ -0,0 +1,67 @@
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use uuid::Uuid;
+
+use crate::utility::error::{AppError, AppResult};
+
+use super::processing::ProcessedPhoto;
+
+/// Photo files in the media directory, named by the photo identifier.
+#[derive(Clone)]
+pub struct PhotoStorage(PathBuf);
+
+#[derive(Debug, Clone, Copy)]
+pub enum PhotoSize {
+    Full,
+    Thumbnail,
+}
+
+impl PhotoStorage {
+    pub fn new(media_dir: &str) -> Self {
+        Self(PathBuf::from(media_dir).join("bikes"))
+    }
+
+    fn path(&self, photo_id: &str, size: PhotoSize) -> PathBuf {
+        match size {
+            PhotoSize::Full => self.0.join(format!("{photo_id}.jpg")),
+            PhotoSize::Thumbnail => self.0.join(format!("{photo_id}_thumbnail.jpg")),
+        }
+    }
+
+    /// Stores the photo under a new identifier.
+    pub async fn save(&self, photo: &ProcessedPhoto) -> AppResult<String> {
+        let photo_id = Uuid::new_v4().to_string();
+        tokio::fs::create_dir_all(&self.0)
+            .await
+            .map_err(anyhow::Error::from)?;
+        tokio::fs::write(self.path(&photo_id, PhotoSize::Full), &photo.photo)
+            .await
+            .map_err(anyhow::Error::from)?;
+        tokio::fs::write(self.path(&photo_id, PhotoSize::Thumbnail), &photo.thumbnail)
+            .await
+            .map_err(anyhow::Error::from)?;
+        Ok(photo_id)
+    }
+
+    pub async fn read(&self, photo_id: &str, size: PhotoSize) -> AppResult<Vec<u8>> {
+        match tokio::fs::read(self.path(photo_id, size)).await {
+            Ok(data) => Ok(data),
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                Err(AppError::NotFound(format!("Photo {photo_id} not found")))
+            }
+            Err(e) => Err(AppError::Other(e.into())),
+        }
+    }
+
+    pub async fn delete(&self, photo_id: &str) -> AppResult<()> {
+        for size in [PhotoSize::Full, PhotoSize::Thumbnail] {
+            match tokio::fs::remove_file(self.path(photo_id, size)).await {
+                Ok(()) => {}
+                Err(e) if e.kind() == ErrorKind::NotFound => {}
+                Err(e) => return Err(AppError::Other(e.into())),
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,13 +1,19 @@
-use chrono::Utc;
-use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet};
+
+use chrono::{NaiveDateTime, Utc};
+use sqlx::{SqliteConnection, SqlitePool};
 
 use crate::utility::{
     db_extensions::IntoModels,
-    db_format::format_date_time,
+    db_format::{format_date, format_date_time, format_timestamp},
     error::{AppError, AppResult},
+    versioning::conflict,
 };
 
-use super::models::{BikeModel, BikePartial, BikeRaw};
+use super::members::models::BikeRole;
+use super::members::repository::MemberRepository;
+use super::models::{BikeModel, BikePartial, BikeRaw, Odometer};
+use super::rides::models::{RideModel, RideRaw};
 
 #[derive(Clone)]
 pub struct BikeRepository(SqlitePool);
@@ -17,26 +23,88 @@ impl BikeRepository {
         Self(pool)
     }
 
-    pub async fn assert_owner(&self, bike_id: i64, user_id: i64) -> AppResult<()> {
-        let something = sqlx::query!(
-            "SELECT id FROM bikes WHERE id = ? AND owner_id = ? AND deleted_at IS NULL",
+    /// Fails unless the user is a member of the bike with at least the required role,
+    /// returns the role of the user.
+    pub async fn assert_role(
+        &self,
+        bike_id: i64,
+        user_id: i64,
+        required: BikeRole,
+    ) -> AppResult<BikeRole> {
+        Self::assert_role_in(&mut *self.0.acquire().await?, bike_id, user_id, required).await
+    }
+
+    pub async fn assert_role_in(
+        conn: &mut SqliteConnection,
+        bike_id: i64,
+        user_id: i64,
+        required: BikeRole,
+    ) -> AppResult<BikeRole> {
+        let role = sqlx::query_scalar!(
+            "SELECT bike_members.role FROM bike_members JOIN bikes ON bikes.id = bike_members.bike_id WHERE bike_members.bike_id = ? AND bike_members.user_id = ? AND bikes.deleted_at IS NULL",
+            bike_id,
+            user_id
+        )
+        .fetch_optional(&mut *conn)
+        .await?
+        .map(|role| role.parse::<BikeRole>())
+        .transpose()?;
+
+        match role {
+            Some(role) if role >= required => Ok(role),
+            _ => Err(AppError::Forbidden),
+        }
+    }
+
+    /// Same as `assert_role`, but also accepts deleted bikes which are still in the trash.
+    pub async fn assert_role_including_deleted(
+        &self,
+        bike_id: i64,
+        user_id: i64,
+        required: BikeRole,
+    ) -> AppResult<BikeRole> {
+        let role = sqlx::query_scalar!(
+            "SELECT role FROM bike_members WHERE bike_id = ? AND user_id = ?",
             bike_id,
             user_id
         )
         .fetch_optional(&self.0)
-        .await?;
+        .await?
+        .map(|role| role.parse::<BikeRole>())
+        .transpose()?;
 
-        match something {
-            Some(_) => Ok(()),
-            None => Err(AppError::Forbidden),
+        match role {
+            Some(role) if role >= required => Ok(role),
+            _ => Err(AppError::Forbidden),
         }
     }
 
-    pub async fn get_all(&self, owner_id: i64) -> AppResult<Vec<BikeModel>> {
+    /// Bikes the user is a member of, in the order they arranged them.
+    pub async fn get_all(&self, user_id: i64) -> AppResult<Vec<BikeModel>> {
         let models = sqlx::query_as!(
             BikeRaw,
-            "SELECT * FROM bikes WHERE owner_id = ? AND deleted_at IS NULL",
-            owner_id
+            r#"SELECT bikes.id AS "id!", bikes.name AS "name!", bikes.description, bikes.deleted_at, bikes.color, bikes.owner_id AS "owner_id!", bikes.strava_gear, bikes.updated_at AS "updated_at!", bikes.version AS "version!", bikes.initial_distance AS "initial_distance!", bikes.initial_distance_date, bikes.bike_type, bikes.frame_size, bikes.wheel_size, bikes.weight, bikes.model_year, bikes.purchase_date, bikes.purchase_price, bikes.currency, bikes.photo, bikes.retired_at FROM bikes JOIN bike_members ON bike_members.bike_id = bikes.id WHERE bike_members.user_id = ? AND bikes.deleted_at IS NULL ORDER BY bike_members.sort_order, bikes.id"#,
+            user_id
+        )
+        .fetch_all(&self.0)
+        .await?
+        .into_models()?;
+
+        Ok(models)
+    }
+
+    /// Bikes changed since the given time, or all bikes the user was added to since then.
+    pub async fn get_changed_since(
+        &self,
+        user_id: i64,
+        since: &NaiveDateTime,
+    ) -> AppResult<Vec<BikeModel>> {
+        let since = format_timestamp(since);
+        let models = sqlx::query_as!(
+            BikeRaw,
+            r#"SELECT id AS "id!", name AS "name!", description, deleted_at, color, owner_id AS "owner_id!", strava_gear, updated_at AS "updated_at!", version AS "version!", initial_distance AS "initial_distance!", initial_distance_date, bike_type, frame_size, wheel_size, weight, model_year, purchase_date, purchase_price, currency, photo, retired_at FROM bikes WHERE id IN (SELECT bike_id FROM bike_members WHERE user_id = ?1) AND (updated_at >= ?2 OR id IN (SELECT bike_id FROM bike_member_changes WHERE user_id = ?1 AND changed_at >= ?2)) ORDER BY updated_at, id"#,
+            user_id,
+            since
         )
         .fetch_all(&self.0)
         .await?
@@ -58,6 +126,20 @@ impl BikeRepository {
         Ok(model)
     }
 
+    pub async fn get_one_in(conn: &mut SqliteConnection, bike_id: i64) -> AppResult<BikeModel> {
+        let model = sqlx::query_as!(
+            BikeRaw,
+            "SELECT * FROM bikes WHERE id = ? AND deleted_at IS NULL",
+            bike_id
+        )
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("No bike found with id {bike_id}")))?
+        .try_into()?;
+
+        Ok(model)
+    }
+
     pub async fn get_by_strava_gear(
         &self,
         owner_id: i64,
@@ -78,86 +160,207 @@ impl BikeRepository {
         Ok(model)
     }
 
-    pub async fn create(&self, owner_id: i64, new: &BikePartial) -> AppResult<BikeModel> {
+    /// Strava gear linked to the bikes of the user.
+    pub async fn get_strava_gears(&self, owner_id: i64) -> AppResult<HashSet<String>> {
+        let gears = sqlx::query_scalar!(
+            r#"SELECT strava_gear AS "strava_gear!" FROM bikes WHERE owner_id = ? AND strava_gear IS NOT NULL AND deleted_at IS NULL"#,
+            owner_id
+        )
+        .fetch_all(&self.0)
+        .await?;
+
+        Ok(gears.into_iter().collect())
+    }
+
+    pub async fn create_in(
+        conn: &mut SqliteConnection,
+        owner_id: i64,
+        new: &BikePartial,
+    ) -> AppResult<BikeModel> {
+        let initial_distance_date = new.initial_distance_date.map(|d| format_date(&d));
+        let bike_type = new.bike_type.map(|t| t.as_str());
+        let purchase_date = new.purchase_date.map(|d| format_date(&d));
         let id = sqlx::query!(
-            "INSERT INTO bikes (name, description, color, strava_gear, owner_id) VALUES (?, ?, ?, ?, ?)",
+            "INSERT INTO bikes (name, description, color, strava_gear, owner_id, initial_distance, initial_distance_date, bike_type, frame_size, wheel_size, weight, model_year, purchase_date, purchase_price, currency) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
             new.name,
             new.description,
             new.color,
             new.strava_gear,
-            owner_id
+            owner_id,
+            new.initial_distance,
+            initial_distance_date,
+            bike_type,
+            new.frame_size,
+            new.wheel_size,
+            new.weight,
+            new.model_year,
+            purchase_date,
+            new.purchase_price,
+            new.currency
         )
-        .execute(&self.0)
+        .execute(&mut *conn)
         .await?
         .last_insert_rowid();
+        MemberRepository::set_in(conn, id, owner_id, BikeRole::Owner).await?;
 
-        let model = BikeModel {
-            id,
-            name: new.name.clone(),
-            description: new.description.clone(),
-            deleted_at: None,
-            color: new.color.clone(),
-            owner_id,
-            strava_gear: new.strava_gear.clone(),
-        };
-
-        Ok(model)
+        Self::get_one_in(conn, id).await
     }
 
-    pub async fn update(
-        &self,
+    /// Updates the bike only if it is still at the given version.
+    pub async fn update_in(
+        conn: &mut SqliteConnection,
         bike_id: i64,
-        owner_id: i64,
         update: &BikePartial,
+        version: i64,
     ) -> AppResult<BikeModel> {
+        let initial_distance_date = update.initial_distance_date.map(|d| format_date(&d));
+        let bike_type = update.bike_type.map(|t| t.as_str());
+        let purchase_date = update.purchase_date.map(|d| format_date(&d));
         let affected = sqlx::query!(
-            "UPDATE bikes SET name = ?, description = ?, color = ?, strava_gear = ? WHERE id = ? AND deleted_at IS NULL",
+            "UPDATE bikes SET name = ?, description = ?, color = ?, strava_gear = ?, initial_distance = ?, initial_distance_date = ?, bike_type = ?, frame_size = ?, wheel_size = ?, weight = ?, model_year = ?, purchase_date = ?, purchase_price = ?, currency = ? WHERE id = ? AND version = ? AND deleted_at IS NULL",
             update.name,
             update.description,
             update.color,
             update.strava_gear,
-            bike_id
+            update.initial_distance,
+            initial_distance_date,
+            bike_type,
+            update.frame_size,
+            update.wheel_size,
+            update.weight,
+            update.model_year,
+            purchase_date,
+            update.purchase_price,
+            update.currency,
+            bike_id,
+            version
         )
-        .execute(&self.0)
+        .execute(&mut *conn)
         .await?
         .rows_affected();
 
         if affected == 0 {
-            return Err(AppError::NotFound(format!(
-                "No bike found with id {bike_id}",
-            )));
+            return Err(Self::missing_or_conflict(conn, bike_id).await);
         }
 
-        let model = BikeModel {
-            id: bike_id,
-            name: update.name.clone(),
-            description: update.description.clone(),
-            deleted_at: None,
-            color: update.color.clone(),
-            owner_id,
-            strava_gear: update.strava_gear.clone(),
-        };
-
-        Ok(model)
+        Self::get_one_in(conn, bike_id).await
     }
 
-    pub async fn delete(&self, bike_id: i64) -> AppResult<()> {
+    /// Deletes the bike only if it is still at the given version, trashing its rides along.
+    /// Returns the rides as they were before.
+    pub async fn delete_in(
+        conn: &mut SqliteConnection,
+        bike_id: i64,
+        version: i64,
+    ) -> AppResult<Vec<RideModel>> {
         let now = format_date_time(&Utc::now().naive_utc());
         let affected = sqlx::query!(
-            "UPDATE bikes SET deleted_at = ? WHERE id = ? AND deleted_at IS NULL",
+            "UPDATE bikes SET deleted_at = ? WHERE id = ? AND version = ? AND deleted_at IS NULL",
+            now,
+            bike_id,
+            version
+        )
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+
+        if affected == 0 {
+            return Err(Self::missing_or_conflict(conn, bike_id).await);
+        }
+
+        let rides = sqlx::query_as!(
+            RideRaw,
+            r#"SELECT id AS "id!", date AS "date!", distance AS "distance!", description, deleted_at, bike_id AS "bike_id!", strava_ride, updated_at AS "updated_at!", version AS "version!", moving_time, rider_id FROM rides WHERE bike_id = ? AND deleted_at IS NULL"#,
+            bike_id
+        )
+        .fetch_all(&mut *conn)
+        .await?
+        .into_models()?;
+
+        // Same time as the bike, so that restoring the bike can tell them apart
+        sqlx::query!(
+            "UPDATE rides SET deleted_at = ? WHERE bike_id = ? AND deleted_at IS NULL",
             now,
             bike_id
         )
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(rides)
+    }
+
+    /// Replaces the photo only if it is still `previous`, `None` when it was changed meanwhile.
+    pub async fn swap_photo(
+        &self,
+        bike_id: i64,
+        previous: Option<&str>,
+        photo: Option<&str>,
+    ) -> AppResult<Option<BikeModel>> {
+        let affected = sqlx::query!(
+            "UPDATE bikes SET photo = ? WHERE id = ? AND deleted_at IS NULL AND photo IS ?",
+            photo,
+            bike_id,
+            previous
+        )
         .execute(&self.0)
         .await?
         .rows_affected();
 
         if affected == 0 {
-            return Err(AppError::NotFound(format!(
-                "No bike found with id {bike_id}",
-            )));
+            return Ok(None);
         }
 
-        Ok(())
+        self.get_one(bike_id).await.map(Some)
+    }
+
+    pub async fn set_retired_in(
+        conn: &mut SqliteConnection,
+        bike_id: i64,
+        retired_at: Option<&NaiveDateTime>,
+    ) -> AppResult<BikeModel> {
+        let retired_at = retired_at.map(format_date_time);
+        sqlx::query!(
+            "UPDATE bikes SET retired_at = ? WHERE id = ? AND deleted_at IS NULL",
+            retired_at,
+            bike_id
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        Self::get_one_in(conn, bike_id).await
+    }
+
+    /// Total distance of non-deleted rides per bike the user is a member of.
+    pub async fn get_rides_distances(&self, user_id: i64) -> AppResult<HashMap<i64, f64>> {
+        let rows = sqlx::query!(
+            r#"SELECT rides.bike_id, SUM(rides.distance) AS "distance!: f64" FROM rides JOIN bike_members ON bike_members.bike_id = rides.bike_id WHERE bike_members.user_id = ? AND rides.deleted_at IS NULL GROUP BY rides.bike_id"#,
+            user_id
+        )
+        .fetch_all(&self.0)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.bike_id, row.distance))
+            .collect())
+    }
+
+    pub async fn get_odometer(&self, bike_id: i64) -> AppResult<Odometer> {
+        let bike = self.get_one(bike_id).await?;
+        let rides_distance = sqlx::query_scalar!(
+            r#"SELECT COALESCE(SUM(distance), 0.0) AS "distance!: f64" FROM rides WHERE bike_id = ? AND deleted_at IS NULL"#,
+            bike_id
+        )
+        .fetch_one(&self.0)
+        .await?;
+
+        Ok(Odometer::new(bike.initial_distance, rides_distance))
+    }
+
+    async fn missing_or_conflict(conn: &mut SqliteConnection, bike_id: i64) -> AppError {
+        match Self::get_one_in(conn, bike_id).await {
+            Ok(current) => conflict(current.version, &current),
+            Err(e) => e,
+        }
     }
 }
@@ -1,3 +1,4 @@
 pub mod models;
 pub mod repository;
 pub mod routes;
+pub mod tracks;
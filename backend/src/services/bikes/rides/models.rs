@@ -1,12 +1,17 @@
-use chrono::{NaiveDate, NaiveDateTime};
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
+use quick_xml::escape::escape;
 use serde::{Deserialize, Serialize};
 
 use crate::utility::{
     db_extensions::Model,
-    db_format::{format_date, format_date_time, parse_date, parse_date_time},
+    db_format::{
+        format_date, format_date_time, format_timestamp, parse_date, parse_date_time,
+        parse_timestamp,
+    },
+    patch::present,
 };
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, sqlx::FromRow)]
 pub struct RideRaw {
     pub id: i64,
     pub date: String,
@@ -15,6 +20,10 @@ pub struct RideRaw {
     pub deleted_at: Option<String>,
     pub bike_id: i64,
     pub strava_ride: Option<i64>,
+    pub updated_at: String,
+    pub version: i64,
+    pub moving_time: Option<i64>,
+    pub rider_id: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,6 +36,16 @@ pub struct RideModel {
     pub deleted_at: Option<NaiveDateTime>,
     pub bike_id: i64,
     pub strava_ride: Option<i64>,
+    #[serde(default)]
+    pub updated_at: NaiveDateTime,
+    #[serde(default)]
+    pub version: i64,
+    /// Moving time in seconds
+    #[serde(default)]
+    pub moving_time: Option<i64>,
+    /// Member of the bike who rode the ride
+    #[serde(default)]
+    pub rider_id: Option<i64>,
 }
 
 impl Model<RideRaw> for RideModel {}
@@ -44,6 +63,10 @@ impl TryFrom<RideRaw> for RideModel {
             deleted_at,
             bike_id: raw.bike_id,
             strava_ride: raw.strava_ride,
+            updated_at: parse_timestamp(&raw.updated_at)?,
+            version: raw.version,
+            moving_time: raw.moving_time,
+            rider_id: raw.rider_id,
         })
     }
 }
@@ -60,6 +83,10 @@ impl From<RideModel> for RideRaw {
             deleted_at,
             bike_id: model.bike_id,
             strava_ride: model.strava_ride,
+            updated_at: format_timestamp(&model.updated_at),
+            version: model.version,
+            moving_time: model.moving_time,
+            rider_id: model.rider_id,
         }
     }
 }
@@ -71,13 +98,314 @@ pub struct RidePartial {
     pub distance: f64,
     pub description: Option<String>,
     pub strava_ride: Option<i64>,
+    #[serde(default)]
+    pub moving_time: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RidePatch {
+    #[serde(default, deserialize_with = "present")]
+    pub date: Option<NaiveDate>,
+    #[serde(default, deserialize_with = "present")]
+    pub distance: Option<f64>,
+    #[serde(default, deserialize_with = "present")]
+    pub description: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub strava_ride: Option<Option<i64>>,
+    #[serde(default, deserialize_with = "present")]
+    pub moving_time: Option<Option<i64>>,
+}
+
+impl RidePatch {
+    pub fn apply(self, ride: &RideModel) -> RidePartial {
+        RidePartial {
+            date: self.date.unwrap_or(ride.date),
+            distance: self.distance.unwrap_or(ride.distance),
+            description: self.description.unwrap_or_else(|| ride.description.clone()),
+            strava_ride: self.strava_ride.unwrap_or(ride.strava_ride),
+            moving_time: self.moving_time.unwrap_or(ride.moving_time),
+        }
+    }
+}
+
+impl AsRef<RideModel> for RideModel {
+    fn as_ref(&self) -> &RideModel {
+        self
+    }
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct RideWithBikeRaw {
+    #[sqlx(flatten)]
+    pub ride: RideRaw,
+    pub bike_name: String,
+    pub bike_color: Option<String>,
+}
+
+/// Ride listed across all bikes of a user, with the bike it belongs to.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RideWithBike {
+    #[serde(flatten)]
+    pub ride: RideModel,
+    pub bike_name: String,
+    pub bike_color: Option<String>,
+}
+
+impl Model<RideWithBikeRaw> for RideWithBike {}
+
+impl TryFrom<RideWithBikeRaw> for RideWithBike {
+    type Error = anyhow::Error;
+    fn try_from(raw: RideWithBikeRaw) -> Result<Self, Self::Error> {
+        Ok(RideWithBike {
+            ride: raw.ride.try_into()?,
+            bike_name: raw.bike_name,
+            bike_color: raw.bike_color,
+        })
+    }
+}
+
+impl AsRef<RideModel> for RideWithBike {
+    fn as_ref(&self) -> &RideModel {
+        &self.ride
+    }
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct RideSearchRaw {
+    #[sqlx(flatten)]
+    pub ride: RideWithBikeRaw,
+    pub snippet: String,
+    pub rank: f64,
+}
+
+/// Ride matching a full-text search, with the matched terms of the description
+/// wrapped in `<mark>` tags. The rest of the snippet is HTML escaped.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RideSearchResult {
+    #[serde(flatten)]
+    pub ride: RideWithBike,
+    pub snippet: String,
+    /// BM25 score, lower is a better match
+    pub rank: f64,
+}
+
+pub const SNIPPET_MATCH_START: char = '\u{2}';
+pub const SNIPPET_MATCH_END: char = '\u{3}';
+
+impl Model<RideSearchRaw> for RideSearchResult {}
+
+impl TryFrom<RideSearchRaw> for RideSearchResult {
+    type Error = anyhow::Error;
+    fn try_from(raw: RideSearchRaw) -> Result<Self, Self::Error> {
+        let snippet = escape(raw.snippet.as_str())
+            .replace(SNIPPET_MATCH_START, "<mark>")
+            .replace(SNIPPET_MATCH_END, "</mark>");
+        Ok(RideSearchResult {
+            ride: raw.ride.try_into()?,
+            snippet,
+            rank: raw.rank,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RideSearchQuery {
+    pub q: String,
+    pub limit: Option<u32>,
+}
+
+/// Turns user input into an FTS5 query where every word has to match as a prefix,
+/// so that operators and quotes in the input cannot break the query syntax.
+pub fn fts_query(text: &str) -> Option<String> {
+    let terms = text
+        .split_whitespace()
+        .map(|term| format!("\"{}\"*", term.replace('"', "\"\"")))
+        .collect::<Vec<_>>();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+/// Ride with its tags, recorded in the history when the tags change.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaggedRide<'a> {
+    #[serde(flatten)]
+    pub ride: &'a RideModel,
+    pub tags: &'a [String],
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RideMove {
+    pub ride_ids: Vec<i64>,
+    pub target_bike_id: i64,
 }
 
 #[derive(Debug, Serialize, Default)]
 #[serde(rename_all = "camelCase")]
-pub struct RideMonth {
+pub struct RideMonth<T = RideModel> {
     pub year: i32,
     pub month: i32,
     pub total_distance: f64,
-    pub rides: Vec<RideModel>,
+    pub rides: Vec<T>,
+}
+
+impl<T: AsRef<RideModel>> RideMonth<T> {
+    /// Groups the rides of a single year into months, starting with December.
+    pub fn group_year(year: i32, rides: Vec<T>) -> Vec<Self> {
+        let mut result = (0..12)
+            .map(|month| RideMonth {
+                year,
+                month: 12 - month,
+                total_distance: 0.0,
+                rides: Vec::new(),
+            })
+            .collect::<Vec<_>>();
+
+        for ride in rides {
+            let month = ride.as_ref().date.month() as usize;
+            result[12 - month].total_distance += ride.as_ref().distance;
+            result[12 - month].rides.push(ride);
+        }
+
+        result
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RideSort {
+    #[default]
+    Date,
+    Distance,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RideFilter {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub min_distance: Option<f64>,
+    pub max_distance: Option<f64>,
+    /// Text searched for in the ride description
+    pub q: Option<String>,
+    pub tag: Option<String>,
+    /// Only rides synced from Strava (`true`) or entered manually (`false`)
+    pub strava: Option<bool>,
+    #[serde(default)]
+    pub sort: RideSort,
+    #[serde(default)]
+    pub order: SortOrder,
+    pub cursor: Option<String>,
+    pub limit: Option<u32>,
+}
+
+/// Tags are compared case-insensitively, so they are stored trimmed and lowercase.
+pub fn normalize_tag(tag: &str) -> Option<String> {
+    let tag = tag.trim().to_lowercase();
+    (!tag.is_empty()).then_some(tag)
+}
+
+/// Position after the last ride of a page, encoded as an opaque string.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RideCursor {
+    pub key: String,
+    pub id: i64,
+}
+
+impl RideCursor {
+    pub fn new(ride: &RideModel, sort: RideSort) -> Self {
+        let key = match sort {
+            RideSort::Date => format_date(&ride.date),
+            RideSort::Distance => ride.distance.to_string(),
+        };
+        RideCursor { key, id: ride.id }
+    }
+
+    pub fn encode(&self) -> String {
+        hex::encode(format!("{}|{}", self.key, self.id))
+    }
+
+    pub fn decode(cursor: &str) -> anyhow::Result<Self> {
+        let decoded = String::from_utf8(hex::decode(cursor)?)?;
+        let (key, id) = decoded
+            .rsplit_once('|')
+            .ok_or_else(|| anyhow::anyhow!("Malformed cursor"))?;
+        Ok(RideCursor {
+            key: key.to_string(),
+            id: id.parse()?,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct RidePage<T = RideModel> {
+    pub rides: Vec<T>,
+    pub next_cursor: Option<RideCursor>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_encoded_cursor() {
+        let cursor = RideCursor {
+            key: String::from("2026-01-01"),
+            id: 42,
+        };
+        assert_eq!(RideCursor::decode(&cursor.encode()).unwrap(), cursor);
+
+        let cursor = RideCursor {
+            key: String::from("a|b"),
+            id: 7,
+        };
+        assert_eq!(RideCursor::decode(&cursor.encode()).unwrap(), cursor);
+    }
+
+    #[test]
+    fn rejects_invalid_cursor() {
+        assert!(RideCursor::decode("").is_err());
+        assert!(RideCursor::decode("not hex").is_err());
+        assert!(RideCursor::decode("abc").is_err());
+        assert!(RideCursor::decode("ff").is_err());
+        assert!(RideCursor::decode(&hex::encode("2026-01-01")).is_err());
+        assert!(RideCursor::decode(&hex::encode("2026-01-01|x")).is_err());
+    }
+
+    #[test]
+    fn matches_every_word_as_prefix() {
+        assert_eq!(
+            fts_query("  morning   ride ").as_deref(),
+            Some(r#""morning"* "ride"*"#)
+        );
+        assert_eq!(fts_query(""), None);
+        assert_eq!(fts_query(" \t "), None);
+    }
+
+    #[test]
+    fn escapes_fts_syntax() {
+        assert_eq!(
+            fts_query(r#"say "hi""#).as_deref(),
+            Some(r#""say"* """hi"""*"#)
+        );
+        assert_eq!(
+            fts_query("a OR b NOT c*").as_deref(),
+            Some(r#""a"* "OR"* "b"* "NOT"* "c*"*"#)
+        );
+        assert_eq!(
+            fts_query("description:x (y) ^z -w").as_deref(),
+            Some(r#""description:x"* "(y)"* "^z"* "-w"*"#)
+        );
+    }
 }
@@ -1,13 +1,20 @@
-use chrono::Utc;
-use sqlx::SqlitePool;
+use chrono::{NaiveDate, NaiveDateTime, Utc};
+use sqlx::{QueryBuilder, Sqlite, SqliteConnection, SqlitePool};
 
 use crate::utility::{
     db_extensions::IntoModels,
-    db_format::{format_date, format_date_time},
+    db_format::{format_date, format_date_time, format_timestamp},
     error::{AppError, AppResult},
+    versioning::conflict,
 };
 
-use super::models::{RideModel, RidePartial, RideRaw};
+use super::models::{
+    normalize_tag, RideCursor, RideFilter, RideModel, RidePage, RidePartial, RideRaw,
+    RideSearchRaw, RideSearchResult, RideSort, RideWithBike, RideWithBikeRaw, SortOrder,
+    SNIPPET_MATCH_END, SNIPPET_MATCH_START,
+};
+
+const MAX_PAGE_SIZE: u32 = 500;
 
 #[derive(Clone)]
 pub struct RideRepository(SqlitePool);
@@ -29,30 +36,113 @@ impl RideRepository {
         Ok(models)
     }
 
-    pub async fn get_all_for_bike(&self, bike_id: i64) -> AppResult<Vec<RideModel>> {
-        let models = sqlx::query_as!(
-            RideRaw,
-            "SELECT * FROM rides WHERE deleted_at IS NULL AND bike_id = ? ORDER BY date DESC, id DESC",
-            bike_id
+    pub async fn get_all_for_bike_between(
+        &self,
+        bike_id: i64,
+        from: &NaiveDate,
+        to: &NaiveDate,
+        order: SortOrder,
+    ) -> AppResult<Vec<RideModel>> {
+        let from = format_date(from);
+        let to = format_date(to);
+        let raws = match order {
+            SortOrder::Asc => sqlx::query_as!(
+                RideRaw,
+                r#"SELECT id AS "id!", date AS "date!", distance AS "distance!", description, deleted_at, bike_id AS "bike_id!", strava_ride, updated_at AS "updated_at!", version AS "version!", moving_time, rider_id FROM rides WHERE deleted_at IS NULL AND bike_id = ? AND date >= ? AND date <= ? ORDER BY date ASC, id ASC"#,
+                bike_id,
+                from,
+                to
+            )
+            .fetch_all(&self.0)
+            .await?,
+            SortOrder::Desc => sqlx::query_as!(
+                RideRaw,
+                r#"SELECT id AS "id!", date AS "date!", distance AS "distance!", description, deleted_at, bike_id AS "bike_id!", strava_ride, updated_at AS "updated_at!", version AS "version!", moving_time, rider_id FROM rides WHERE deleted_at IS NULL AND bike_id = ? AND date >= ? AND date <= ? ORDER BY date DESC, id DESC"#,
+                bike_id,
+                from,
+                to
+            )
+            .fetch_all(&self.0)
+            .await?,
+        };
+        let models = raws.into_models()?;
+
+        Ok(models)
+    }
+
+    pub async fn search(&self, bike_id: i64, filter: &RideFilter) -> AppResult<RidePage> {
+        let mut query = QueryBuilder::new("SELECT * FROM rides WHERE rides.deleted_at IS NULL");
+        query.push(" AND rides.bike_id = ").push_bind(bike_id);
+        push_ride_filter(&mut query, filter)?;
+
+        let rides = query
+            .build_query_as::<RideRaw>()
+            .fetch_all(&self.0)
+            .await?
+            .into_models()?;
+
+        Ok(into_page(rides, filter))
+    }
+
+    /// Rides of all bikes the user is a member of.
+    pub async fn search_for_user(
+        &self,
+        user_id: i64,
+        filter: &RideFilter,
+    ) -> AppResult<RidePage<RideWithBike>> {
+        let mut query = QueryBuilder::new(
+            "SELECT rides.*, bikes.name AS bike_name, bikes.color AS bike_color FROM rides JOIN bikes ON bikes.id = rides.bike_id WHERE rides.deleted_at IS NULL AND bikes.deleted_at IS NULL",
+        );
+        query
+            .push(" AND bikes.id IN (SELECT bike_id FROM bike_members WHERE user_id = ")
+            .push_bind(user_id)
+            .push(")");
+        push_ride_filter(&mut query, filter)?;
+
+        let rides = query
+            .build_query_as::<RideWithBikeRaw>()
+            .fetch_all(&self.0)
+            .await?
+            .into_models()?;
+
+        Ok(into_page(rides, filter))
+    }
+
+    pub async fn full_text_search(
+        &self,
+        user_id: i64,
+        fts_query: &str,
+        limit: u32,
+    ) -> AppResult<Vec<RideSearchResult>> {
+        let start = SNIPPET_MATCH_START.to_string();
+        let end = SNIPPET_MATCH_END.to_string();
+        let results = sqlx::query_as::<_, RideSearchRaw>(
+            "SELECT rides.*, bikes.name AS bike_name, bikes.color AS bike_color, snippet(rides_fts, 0, ?, ?, '…', 16) AS snippet, bm25(rides_fts) AS rank FROM rides_fts JOIN rides ON rides.id = rides_fts.rowid JOIN bikes ON bikes.id = rides.bike_id WHERE rides_fts MATCH ? AND rides.deleted_at IS NULL AND bikes.deleted_at IS NULL AND bikes.id IN (SELECT bike_id FROM bike_members WHERE user_id = ?) ORDER BY rank, rides.date DESC LIMIT ?",
         )
+        .bind(start)
+        .bind(end)
+        .bind(fts_query)
+        .bind(user_id)
+        .bind(page_size(limit) as i64)
         .fetch_all(&self.0)
         .await?
         .into_models()?;
 
-        Ok(models)
+        Ok(results)
     }
 
-    pub async fn get_all_for_bike_with_date(
+    /// Rides of the user's bikes changed since the timestamp, including deleted ones.
+    pub async fn get_changed_since(
         &self,
-        bike_id: i64,
-        date: &str,
+        user_id: i64,
+        since: &NaiveDateTime,
     ) -> AppResult<Vec<RideModel>> {
-        let starts_with = format!("{date}%");
+        let since = format_timestamp(since);
         let models = sqlx::query_as!(
             RideRaw,
-            "SELECT * FROM rides WHERE deleted_at IS NULL AND bike_id = ? AND date LIKE ? ORDER BY date DESC, id DESC",
-            bike_id,
-            starts_with
+            r#"SELECT id AS "id!", date AS "date!", distance AS "distance!", description, deleted_at, bike_id AS "bike_id!", strava_ride, updated_at AS "updated_at!", version AS "version!", moving_time, rider_id FROM rides WHERE bike_id IN (SELECT bike_id FROM bike_members WHERE user_id = ?1) AND (updated_at >= ?2 OR bike_id IN (SELECT bike_id FROM bike_member_changes WHERE user_id = ?1 AND changed_at >= ?2)) ORDER BY updated_at, id"#,
+            user_id,
+            since
         )
         .fetch_all(&self.0)
         .await?
@@ -61,14 +151,26 @@ impl RideRepository {
         Ok(models)
     }
 
-    pub async fn get_one(&self, ride_id: i64) -> AppResult<RideModel> {
+    pub async fn get_one_including_deleted(&self, ride_id: i64) -> AppResult<RideModel> {
+        let model = sqlx::query_as!(RideRaw, "SELECT * FROM rides WHERE id = ?", ride_id)
+            .fetch_optional(&self.0)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("No ride found with id {ride_id}")))?
+            .try_into()?;
+
+        Ok(model)
+    }
+
+    pub async fn get_one_for_bike(&self, bike_id: i64, ride_id: i64) -> AppResult<RideModel> {
         let model = sqlx::query_as!(
             RideRaw,
-            "SELECT * FROM rides WHERE id = ? AND deleted_at IS NULL",
-            ride_id
+            "SELECT * FROM rides WHERE id = ? AND bike_id = ? AND deleted_at IS NULL",
+            ride_id,
+            bike_id
         )
-        .fetch_one(&self.0)
+        .fetch_optional(&self.0)
         .await?
+        .ok_or_else(|| AppError::NotFound(format!("No ride found with id {ride_id}")))?
         .try_into()?;
 
         Ok(model)
@@ -81,7 +183,7 @@ impl RideRepository {
     ) -> AppResult<Option<RideModel>> {
         let model = sqlx::query_as!(
             RideRaw,
-            "SELECT * FROM rides WHERE bike_id = ? AND strava_ride = ?",
+            r#"SELECT id AS "id!", date AS "date!", distance AS "distance!", description, deleted_at, bike_id AS "bike_id!", strava_ride, updated_at AS "updated_at!", version AS "version!", moving_time, rider_id FROM rides WHERE bike_id = ? AND strava_ride = ?"#,
             bike_id,
             strava_ride
         )
@@ -93,79 +195,298 @@ impl RideRepository {
         Ok(model)
     }
 
-    pub async fn create(&self, bike_id: i64, new: &RidePartial) -> AppResult<RideModel> {
+    pub async fn try_get_by_idempotency_key(
+        &self,
+        bike_id: i64,
+        key: &str,
+    ) -> AppResult<Option<RideModel>> {
+        Self::try_get_by_idempotency_key_in(&mut *self.0.acquire().await?, bike_id, key).await
+    }
+
+    // Variants running on a given connection, so that several changes can share a transaction
+
+    pub async fn get_one_in(conn: &mut SqliteConnection, ride_id: i64) -> AppResult<RideModel> {
+        let model = sqlx::query_as!(
+            RideRaw,
+            "SELECT * FROM rides WHERE id = ? AND deleted_at IS NULL",
+            ride_id
+        )
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("No ride found with id {ride_id}")))?
+        .try_into()?;
+
+        Ok(model)
+    }
+
+    pub async fn create_in(
+        conn: &mut SqliteConnection,
+        bike_id: i64,
+        rider_id: Option<i64>,
+        new: &RidePartial,
+    ) -> AppResult<RideModel> {
         let date = format_date(&new.date);
         let id = sqlx::query!(
-            "INSERT INTO rides (date, distance, description, bike_id, strava_ride) VALUES (?, ?, ?, ?, ?)",
+            "INSERT INTO rides (date, distance, description, bike_id, strava_ride, moving_time, rider_id) VALUES (?, ?, ?, ?, ?, ?, ?)",
             date,
             new.distance,
             new.description,
             bike_id,
-            new.strava_ride
+            new.strava_ride,
+            new.moving_time,
+            rider_id
         )
-        .execute(&self.0)
+        .execute(&mut *conn)
         .await?
         .last_insert_rowid();
 
-        let model = RideModel {
-            id,
-            date: new.date,
-            distance: new.distance,
-            description: new.description.clone(),
-            deleted_at: None,
+        Self::get_one_in(conn, id).await
+    }
+
+    pub async fn try_get_by_idempotency_key_in(
+        conn: &mut SqliteConnection,
+        bike_id: i64,
+        key: &str,
+    ) -> AppResult<Option<RideModel>> {
+        let model = sqlx::query_as!(
+            RideRaw,
+            "SELECT * FROM rides WHERE id = (SELECT ride_id FROM ride_idempotency_keys WHERE bike_id = ? AND key = ?)",
             bike_id,
-            strava_ride: None,
-        };
+            key
+        )
+        .fetch_optional(&mut *conn)
+        .await?
+        .map(|r| r.try_into())
+        .transpose()?;
 
         Ok(model)
     }
 
-    pub async fn update(&self, ride_id: i64, update: &RidePartial) -> AppResult<RideModel> {
+    /// Fails with a conflict when a concurrent request created a ride with the same key.
+    pub async fn create_idempotent_in(
+        conn: &mut SqliteConnection,
+        bike_id: i64,
+        rider_id: Option<i64>,
+        key: &str,
+        new: &RidePartial,
+    ) -> AppResult<(RideModel, bool)> {
+        if let Some(existing) = Self::try_get_by_idempotency_key_in(conn, bike_id, key).await? {
+            return Ok((existing, false));
+        }
+
+        let model = Self::create_in(conn, bike_id, rider_id, new).await?;
+        let now = format_date_time(&Utc::now().naive_utc());
+        let inserted = sqlx::query!(
+            "INSERT INTO ride_idempotency_keys (bike_id, key, ride_id, created_at) VALUES (?, ?, ?, ?) ON CONFLICT DO NOTHING",
+            bike_id,
+            key,
+            model.id,
+            now
+        )
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+
+        if inserted == 0 {
+            return Err(AppError::Conflict(format!(
+                "Ride with idempotency key {key} is already being created"
+            )));
+        }
+
+        Ok((model, true))
+    }
+
+    /// Updates the ride only if it is still at the given version.
+    pub async fn update_in(
+        conn: &mut SqliteConnection,
+        ride_id: i64,
+        update: &RidePartial,
+        version: i64,
+    ) -> AppResult<RideModel> {
         let date = format_date(&update.date);
         let affected = sqlx::query!(
-            "UPDATE rides SET date = ?, distance = ?, description = ?, strava_ride = ? WHERE id = ? AND deleted_at IS NULL",
+            "UPDATE rides SET date = ?, distance = ?, description = ?, strava_ride = ?, moving_time = ? WHERE id = ? AND version = ? AND deleted_at IS NULL",
             date,
             update.distance,
             update.description,
             update.strava_ride,
-            ride_id
+            update.moving_time,
+            ride_id,
+            version
         )
-        .execute(&self.0)
+        .execute(&mut *conn)
         .await?
         .rows_affected();
 
         if affected == 0 {
-            return Err(AppError::NotFound(format!(
-                "No ride found with id {ride_id}",
-            )));
+            return Err(Self::missing_or_conflict(conn, ride_id).await);
         }
 
-        self.get_one(ride_id).await
+        Self::get_one_in(conn, ride_id).await
     }
 
-    pub async fn delete(&self, ride_id: i64) -> AppResult<()> {
+    /// Deletes the ride only if it is still at the given version.
+    pub async fn delete_in(
+        conn: &mut SqliteConnection,
+        ride_id: i64,
+        version: i64,
+    ) -> AppResult<()> {
         let now = format_date_time(&Utc::now().naive_utc());
         let affected = sqlx::query!(
-            "UPDATE rides SET deleted_at = ? WHERE id = ? AND deleted_at IS NULL",
+            "UPDATE rides SET deleted_at = ? WHERE id = ? AND version = ? AND deleted_at IS NULL",
             now,
-            ride_id
+            ride_id,
+            version
         )
-        .execute(&self.0)
+        .execute(&mut *conn)
         .await?
         .rows_affected();
 
         if affected == 0 {
-            return Err(AppError::NotFound(format!(
-                "No ride found with id {ride_id}",
-            )));
+            return Err(Self::missing_or_conflict(conn, ride_id).await);
         }
 
         Ok(())
     }
 
+    async fn missing_or_conflict(conn: &mut SqliteConnection, ride_id: i64) -> AppError {
+        match Self::get_one_in(conn, ride_id).await {
+            Ok(current) => conflict(current.version, &current),
+            Err(e) => e,
+        }
+    }
+
+    /// Moves the ride to another bike, a bike can not have the same Strava activity twice.
+    pub async fn move_in(
+        conn: &mut SqliteConnection,
+        ride_id: i64,
+        bike_id: i64,
+    ) -> AppResult<RideModel> {
+        let ride = Self::get_one_in(conn, ride_id).await?;
+        if let Some(strava_ride) = ride.strava_ride {
+            let duplicate = sqlx::query!(
+                "SELECT id FROM rides WHERE bike_id = ? AND strava_ride = ? AND id != ? AND deleted_at IS NULL",
+                bike_id,
+                strava_ride,
+                ride_id
+            )
+            .fetch_optional(&mut *conn)
+            .await?;
+            if duplicate.is_some() {
+                return Err(AppError::Conflict(format!(
+                    "Bike {bike_id} already has the Strava activity {strava_ride}"
+                )));
+            }
+        }
+
+        sqlx::query!(
+            "UPDATE rides SET bike_id = ? WHERE id = ? AND deleted_at IS NULL",
+            bike_id,
+            ride_id
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        Self::get_one_in(conn, ride_id).await
+    }
+
+    /// Adds the tag only if the ride is still at the given version, bumping its version.
+    pub async fn add_tag_in(
+        conn: &mut SqliteConnection,
+        ride_id: i64,
+        version: i64,
+        tag: &str,
+    ) -> AppResult<(RideModel, Vec<String>)> {
+        let tag =
+            normalize_tag(tag).ok_or_else(|| AppError::BadRequest(String::from("Empty tag")))?;
+        let ride = Self::touch_in(conn, ride_id, version).await?;
+        sqlx::query!(
+            "INSERT OR IGNORE INTO ride_tags (ride_id, tag) VALUES (?, ?)",
+            ride_id,
+            tag
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        let tags = Self::get_tags_in(conn, ride_id).await?;
+        Ok((ride, tags))
+    }
+
+    pub async fn get_tags(&self, ride_id: i64) -> AppResult<Vec<String>> {
+        let mut conn = self.0.acquire().await?;
+        Self::get_tags_in(&mut conn, ride_id).await
+    }
+
+    pub async fn get_tags_in(conn: &mut SqliteConnection, ride_id: i64) -> AppResult<Vec<String>> {
+        let tags = sqlx::query!(
+            "SELECT tag FROM ride_tags WHERE ride_id = ? ORDER BY tag",
+            ride_id
+        )
+        .fetch_all(&mut *conn)
+        .await?
+        .into_iter()
+        .map(|r| r.tag)
+        .collect();
+
+        Ok(tags)
+    }
+
+    /// Replaces the tags only if the ride is still at the given version, bumping its version.
+    pub async fn set_tags_in(
+        conn: &mut SqliteConnection,
+        ride_id: i64,
+        version: i64,
+        tags: &[String],
+    ) -> AppResult<(RideModel, Vec<String>)> {
+        let mut tags = tags
+            .iter()
+            .filter_map(|tag| normalize_tag(tag))
+            .collect::<Vec<_>>();
+        tags.sort();
+        tags.dedup();
+
+        let ride = Self::touch_in(conn, ride_id, version).await?;
+        sqlx::query!("DELETE FROM ride_tags WHERE ride_id = ?", ride_id)
+            .execute(&mut *conn)
+            .await?;
+        for tag in &tags {
+            sqlx::query!(
+                "INSERT INTO ride_tags (ride_id, tag) VALUES (?, ?)",
+                ride_id,
+                tag
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Ok((ride, tags))
+    }
+
+    /// Bumps the version and update time of the ride for changes stored outside of its row.
+    async fn touch_in(
+        conn: &mut SqliteConnection,
+        ride_id: i64,
+        version: i64,
+    ) -> AppResult<RideModel> {
+        let affected = sqlx::query!(
+            "UPDATE rides SET version = version + 1, updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = ? AND version = ? AND deleted_at IS NULL",
+            ride_id,
+            version
+        )
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+
+        if affected == 0 {
+            return Err(Self::missing_or_conflict(conn, ride_id).await);
+        }
+
+        Self::get_one_in(conn, ride_id).await
+    }
+
     pub async fn active_years(&self, bike_id: i64) -> AppResult<Vec<i32>> {
         let years = sqlx::query!(
-            "SELECT DISTINCT strftime('%Y', date) as year FROM rides WHERE bike_id = ? AND deleted_at IS NULL ORDER BY year DESC",
+            r#"SELECT DISTINCT strftime('%Y', date) AS "year: String" FROM rides WHERE bike_id = ? AND deleted_at IS NULL ORDER BY 1 DESC"#,
             bike_id
         )
         .fetch_all(&self.0)
@@ -179,12 +500,19 @@ impl RideRepository {
         Ok(years.map_err(anyhow::Error::from)?)
     }
 
-    pub async fn total_distance(&self, bike_id: i64, year: u32) -> AppResult<f64> {
-        let starts_with = format!("{year}%");
+    pub async fn total_distance(
+        &self,
+        bike_id: i64,
+        from: &NaiveDate,
+        to: &NaiveDate,
+    ) -> AppResult<f64> {
+        let from = format_date(from);
+        let to = format_date(to);
         let total = sqlx::query!(
-            "SELECT SUM(distance) as total FROM rides WHERE bike_id = ? AND date LIKE ? AND deleted_at IS NULL",
+            "SELECT SUM(distance) as \"total: f64\" FROM rides WHERE bike_id = ? AND date >= ? AND date <= ? AND deleted_at IS NULL",
             bike_id,
-            starts_with
+            from,
+            to
         )
         .fetch_one(&self.0)
         .await?
@@ -193,3 +521,122 @@ impl RideRepository {
         Ok(total.unwrap_or(0.0))
     }
 }
+
+/// Drops the extra ride fetched by `push_ride_filter` and remembers where the next page starts.
+fn into_page<T: AsRef<RideModel>>(mut rides: Vec<T>, filter: &RideFilter) -> RidePage<T> {
+    let next_cursor = match filter.limit {
+        Some(limit) if rides.len() > page_size(limit) => {
+            rides.truncate(page_size(limit));
+            rides
+                .last()
+                .map(|ride| RideCursor::new(ride.as_ref(), filter.sort))
+        }
+        _ => None,
+    };
+
+    RidePage { rides, next_cursor }
+}
+
+fn page_size(limit: u32) -> usize {
+    limit.clamp(1, MAX_PAGE_SIZE) as usize
+}
+
+/// Appends the filter conditions, ordering and limit to a query over `rides`.
+/// One extra ride is requested so that the caller knows whether another page exists.
+fn push_ride_filter(query: &mut QueryBuilder<'_, Sqlite>, filter: &RideFilter) -> AppResult<()> {
+    if let Some(from) = &filter.from {
+        query
+            .push(" AND rides.date >= ")
+            .push_bind(format_date(from));
+    }
+    if let Some(to) = &filter.to {
+        query.push(" AND rides.date <= ").push_bind(format_date(to));
+    }
+    if let Some(min_distance) = filter.min_distance {
+        query
+            .push(" AND rides.distance >= ")
+            .push_bind(min_distance);
+    }
+    if let Some(max_distance) = filter.max_distance {
+        query
+            .push(" AND rides.distance <= ")
+            .push_bind(max_distance);
+    }
+    if let Some(text) = filter.q.as_deref().map(str::trim).filter(|q| !q.is_empty()) {
+        let pattern = text
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        query
+            .push(" AND rides.description LIKE ")
+            .push_bind(format!("%{pattern}%"))
+            .push(" ESCAPE '\\'");
+    }
+    if let Some(tag) = filter.tag.as_deref().and_then(normalize_tag) {
+        query
+            .push(" AND EXISTS (SELECT 1 FROM ride_tags WHERE ride_tags.ride_id = rides.id AND ride_tags.tag = ")
+            .push_bind(tag)
+            .push(")");
+    }
+    match filter.strava {
+        Some(true) => {
+            query.push(" AND rides.strava_ride IS NOT NULL");
+        }
+        Some(false) => {
+            query.push(" AND rides.strava_ride IS NULL");
+        }
+        None => {}
+    }
+
+    let column = match filter.sort {
+        RideSort::Date => "rides.date",
+        RideSort::Distance => "rides.distance",
+    };
+    let (comparison, direction) = match filter.order {
+        SortOrder::Asc => (">", "ASC"),
+        SortOrder::Desc => ("<", "DESC"),
+    };
+
+    if let Some(cursor) = &filter.cursor {
+        let cursor = RideCursor::decode(cursor)
+            .map_err(|e| AppError::BadRequest(format!("Invalid cursor: {e}")))?;
+        query.push(format!(" AND ({column} {comparison} "));
+        push_cursor_key(query, &cursor, filter.sort)?;
+        query.push(format!(" OR ({column} = "));
+        push_cursor_key(query, &cursor, filter.sort)?;
+        query
+            .push(format!(" AND rides.id {comparison} "))
+            .push_bind(cursor.id)
+            .push("))");
+    }
+
+    query.push(format!(
+        " ORDER BY {column} {direction}, rides.id {direction}"
+    ));
+    if let Some(limit) = filter.limit {
+        query.push(" LIMIT ").push_bind(page_size(limit) as i64 + 1);
+    }
+
+    Ok(())
+}
+
+fn push_cursor_key(
+    query: &mut QueryBuilder<'_, Sqlite>,
+    cursor: &RideCursor,
+    sort: RideSort,
+) -> AppResult<()> {
+    match sort {
+        RideSort::Date => {
+            query.push_bind(cursor.key.clone());
+        }
+        RideSort::Distance => {
+            let distance = cursor
+                .key
+                .parse::<f64>()
+                .map_err(|e| AppError::BadRequest(format!("Invalid cursor: {e}")))?;
+            query.push_bind(distance);
+        }
+    }
+
+    Ok(())
+}
@@ -1,53 +1,170 @@
-use axum::extract::{Path, State};
-use axum::http::StatusCode;
-use axum::routing::{delete, get, post, put};
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use axum::routing::{delete, get, patch, post, put};
 use axum::{Extension, Json, Router};
-use chrono::Datelike;
+use chrono::NaiveDate;
+use sqlx::SqlitePool;
 
 use crate::services::auth::models::SessionModel;
+use crate::services::bikes::members::models::BikeRole;
 use crate::services::bikes::repository::BikeRepository;
-use crate::utility::error::AppResult;
+use crate::services::history::models::{Actor, HistoryAction, HistoryEntity, HistoryModel};
+use crate::services::history::repository::HistoryRepository;
+use crate::utility::error::{AppError, AppResult};
 use crate::utility::state::AppState;
+use crate::utility::versioning::{assert_version, etag_headers, if_match};
 
-use super::models::{RideModel, RideMonth, RidePartial};
+use super::models::{
+    RideCursor, RideFilter, RideModel, RideMonth, RideMove, RidePartial, RidePatch, SortOrder,
+    TaggedRide,
+};
 use super::repository::RideRepository;
+use super::tracks;
 
 pub fn router_with_auth() -> Router<AppState> {
     Router::new()
         .route("/", get(get_all_rides))
         .route("/", post(create_ride))
+        .route("/move", post(move_rides))
         .route("/years", get(get_active_years))
         .route("/monthly/{year}", get(get_monthly_rides))
         .route("/{year}/{month}", get(get_month))
         .route("/{id}", get(get_ride))
         .route("/{id}", put(update_ride))
+        .route("/{id}", patch(patch_ride))
         .route("/{id}", delete(delete_ride))
+        .route("/{id}/history", get(get_history))
+        .route("/{id}/tags", get(get_tags))
+        .route("/{id}/tags", put(set_tags))
         .route("/total/{year}", get(total_distance))
+        .merge(tracks::routes::router_with_auth())
 }
 
+pub const NEXT_CURSOR_HEADER: &str = "x-next-cursor";
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 255;
+
 async fn get_all_rides(
     State(bike_repo): State<BikeRepository>,
     State(ride_repo): State<RideRepository>,
     Path(bike_id): Path<i64>,
+    Query(filter): Query<RideFilter>,
     Extension(session): Extension<SessionModel>,
-) -> AppResult<Json<Vec<RideModel>>> {
-    bike_repo.assert_owner(bike_id, session.user_id).await?;
+) -> AppResult<(HeaderMap, Json<Vec<RideModel>>)> {
+    bike_repo
+        .assert_role(bike_id, session.user_id, BikeRole::Viewer)
+        .await?;
 
-    let models = ride_repo.get_all_for_bike(bike_id).await?;
-    Ok(Json(models))
+    let page = ride_repo.search(bike_id, &filter).await?;
+    let headers = cursor_headers(page.next_cursor.as_ref())?;
+    Ok((headers, Json(page.rides)))
 }
 
 async fn create_ride(
     State(bike_repo): State<BikeRepository>,
     State(ride_repo): State<RideRepository>,
+    State(pool): State<SqlitePool>,
     Path(bike_id): Path<i64>,
     Extension(session): Extension<SessionModel>,
+    request_headers: HeaderMap,
     Json(payload): Json<RidePartial>,
-) -> AppResult<(StatusCode, Json<RideModel>)> {
-    bike_repo.assert_owner(bike_id, session.user_id).await?;
+) -> AppResult<(StatusCode, HeaderMap, Json<RideModel>)> {
+    bike_repo
+        .assert_role(bike_id, session.user_id, BikeRole::Rider)
+        .await?;
+
+    let rider_id = Some(session.user_id);
+    let mut tx = pool.begin().await?;
+    let (model, created) = match idempotency_key(&request_headers)? {
+        Some(key) => {
+            match RideRepository::create_idempotent_in(&mut tx, bike_id, rider_id, key, &payload)
+                .await
+            {
+                Err(AppError::Conflict(_)) => {
+                    // Concurrent request with the same key won
+                    tx.rollback().await?;
+                    let existing = ride_repo
+                        .try_get_by_idempotency_key(bike_id, key)
+                        .await?
+                        .ok_or_else(|| anyhow::anyhow!("Missing ride for idempotency key {key}"))?;
+                    return Ok(replayed(existing));
+                }
+                created => created?,
+            }
+        }
+        None => (
+            RideRepository::create_in(&mut tx, bike_id, rider_id, &payload).await?,
+            true,
+        ),
+    };
+
+    if !created {
+        return Ok(replayed(model));
+    }
+
+    HistoryRepository::record_in(
+        &mut tx,
+        HistoryEntity::Ride,
+        model.id,
+        HistoryAction::Create,
+        Actor::User(session.user_id),
+        None,
+        Some(&model),
+    )
+    .await?;
+    tx.commit().await?;
+    Ok((StatusCode::CREATED, HeaderMap::new(), Json(model)))
+}
+
+/// Response for a retried creation, with the ride created by the first attempt.
+fn replayed(model: RideModel) -> (StatusCode, HeaderMap, Json<RideModel>) {
+    let mut headers = HeaderMap::new();
+    headers.insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+    (StatusCode::CREATED, headers, Json(model))
+}
+
+async fn move_rides(
+    State(pool): State<SqlitePool>,
+    Path(bike_id): Path<i64>,
+    Extension(session): Extension<SessionModel>,
+    Json(payload): Json<RideMove>,
+) -> AppResult<Json<Vec<RideModel>>> {
+    let mut tx = pool.begin().await?;
+    BikeRepository::assert_role_in(&mut tx, bike_id, session.user_id, BikeRole::Owner).await?;
+    BikeRepository::assert_role_in(
+        &mut tx,
+        payload.target_bike_id,
+        session.user_id,
+        BikeRole::Owner,
+    )
+    .await?;
 
-    let model = ride_repo.create(bike_id, &payload).await?;
-    Ok((StatusCode::CREATED, Json(model)))
+    let mut moved = Vec::with_capacity(payload.ride_ids.len());
+    for ride_id in payload.ride_ids {
+        let before = RideRepository::get_one_in(&mut tx, ride_id).await?;
+        if before.bike_id != bike_id {
+            return Err(AppError::NotFound(format!(
+                "No ride found with id {ride_id}"
+            )));
+        }
+
+        let after = RideRepository::move_in(&mut tx, ride_id, payload.target_bike_id).await?;
+        HistoryRepository::record_in(
+            &mut tx,
+            HistoryEntity::Ride,
+            ride_id,
+            HistoryAction::Update,
+            Actor::User(session.user_id),
+            Some(&before),
+            Some(&after),
+        )
+        .await?;
+        moved.push(after);
+    }
+
+    tx.commit().await?;
+    Ok(Json(moved))
 }
 
 async fn get_ride(
@@ -55,45 +172,142 @@ async fn get_ride(
     State(ride_repo): State<RideRepository>,
     Path((bike_id, ride_id)): Path<(i64, i64)>,
     Extension(session): Extension<SessionModel>,
-) -> AppResult<Json<RideModel>> {
-    bike_repo.assert_owner(bike_id, session.user_id).await?;
+) -> AppResult<(HeaderMap, Json<RideModel>)> {
+    bike_repo
+        .assert_role(bike_id, session.user_id, BikeRole::Viewer)
+        .await?;
 
-    let model = ride_repo.get_one(ride_id).await?;
-    Ok(Json(model))
+    let model = ride_repo.get_one_for_bike(bike_id, ride_id).await?;
+    Ok((etag_headers(model.version), Json(model)))
 }
 
 async fn update_ride(
     State(bike_repo): State<BikeRepository>,
     State(ride_repo): State<RideRepository>,
+    State(pool): State<SqlitePool>,
     Path((bike_id, ride_id)): Path<(i64, i64)>,
     Extension(session): Extension<SessionModel>,
+    headers: HeaderMap,
     Json(payload): Json<RidePartial>,
-) -> AppResult<Json<RideModel>> {
-    bike_repo.assert_owner(bike_id, session.user_id).await?;
+) -> AppResult<(HeaderMap, Json<RideModel>)> {
+    let role = bike_repo
+        .assert_role(bike_id, session.user_id, BikeRole::Rider)
+        .await?;
+
+    let expected = if_match(&headers)?;
+    let before = ride_repo.get_one_for_bike(bike_id, ride_id).await?;
+    role.assert_can_edit_ride(&before, session.user_id)?;
+    assert_version(expected, before.version, &before)?;
+    save_update(&pool, &session, before, &payload).await
+}
+
+async fn patch_ride(
+    State(bike_repo): State<BikeRepository>,
+    State(ride_repo): State<RideRepository>,
+    State(pool): State<SqlitePool>,
+    Path((bike_id, ride_id)): Path<(i64, i64)>,
+    Extension(session): Extension<SessionModel>,
+    headers: HeaderMap,
+    Json(patch): Json<RidePatch>,
+) -> AppResult<(HeaderMap, Json<RideModel>)> {
+    let role = bike_repo
+        .assert_role(bike_id, session.user_id, BikeRole::Rider)
+        .await?;
+
+    let expected = if_match(&headers)?;
+    let before = ride_repo.get_one_for_bike(bike_id, ride_id).await?;
+    role.assert_can_edit_ride(&before, session.user_id)?;
+    assert_version(expected, before.version, &before)?;
+    let payload = patch.apply(&before);
+    save_update(&pool, &session, before, &payload).await
+}
 
-    let model = ride_repo.update(ride_id, &payload).await?;
-    Ok(Json(model))
+async fn save_update(
+    pool: &SqlitePool,
+    session: &SessionModel,
+    before: RideModel,
+    payload: &RidePartial,
+) -> AppResult<(HeaderMap, Json<RideModel>)> {
+    let mut tx = pool.begin().await?;
+    let model = RideRepository::update_in(&mut tx, before.id, payload, before.version).await?;
+    HistoryRepository::record_in(
+        &mut tx,
+        HistoryEntity::Ride,
+        before.id,
+        HistoryAction::Update,
+        Actor::User(session.user_id),
+        Some(&before),
+        Some(&model),
+    )
+    .await?;
+    tx.commit().await?;
+    Ok((etag_headers(model.version), Json(model)))
 }
 
 async fn delete_ride(
     State(bike_repo): State<BikeRepository>,
     State(ride_repo): State<RideRepository>,
+    State(pool): State<SqlitePool>,
     Path((bike_id, ride_id)): Path<(i64, i64)>,
     Extension(session): Extension<SessionModel>,
+    headers: HeaderMap,
 ) -> AppResult<StatusCode> {
-    bike_repo.assert_owner(bike_id, session.user_id).await?;
+    let role = bike_repo
+        .assert_role(bike_id, session.user_id, BikeRole::Rider)
+        .await?;
 
-    ride_repo.delete(ride_id).await?;
+    let expected = if_match(&headers)?;
+    let before = ride_repo.get_one_for_bike(bike_id, ride_id).await?;
+    role.assert_can_edit_ride(&before, session.user_id)?;
+    assert_version(expected, before.version, &before)?;
+    let mut tx = pool.begin().await?;
+    RideRepository::delete_in(&mut tx, ride_id, before.version).await?;
+    HistoryRepository::record_in(
+        &mut tx,
+        HistoryEntity::Ride,
+        ride_id,
+        HistoryAction::Delete,
+        Actor::User(session.user_id),
+        Some(&before),
+        None,
+    )
+    .await?;
+    tx.commit().await?;
     Ok(StatusCode::NO_CONTENT)
 }
 
+async fn get_history(
+    State(bike_repo): State<BikeRepository>,
+    State(ride_repo): State<RideRepository>,
+    State(history_repo): State<HistoryRepository>,
+    Path((bike_id, ride_id)): Path<(i64, i64)>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<Json<Vec<HistoryModel>>> {
+    bike_repo
+        .assert_role_including_deleted(bike_id, session.user_id, BikeRole::Viewer)
+        .await?;
+
+    // History stays available after the ride or its bike is deleted
+    let ride = ride_repo.get_one_including_deleted(ride_id).await?;
+    if ride.bike_id != bike_id {
+        return Err(AppError::NotFound(format!(
+            "No ride found with id {ride_id}"
+        )));
+    }
+
+    let history = history_repo.get_all(HistoryEntity::Ride, ride.id).await?;
+    Ok(Json(history))
+}
+
 async fn get_active_years(
     State(bike_repo): State<BikeRepository>,
     State(ride_repo): State<RideRepository>,
     Path(bike_id): Path<i64>,
     Extension(session): Extension<SessionModel>,
 ) -> AppResult<Json<Vec<i32>>> {
-    bike_repo.assert_owner(bike_id, session.user_id).await?;
+    bike_repo
+        .assert_role(bike_id, session.user_id, BikeRole::Viewer)
+        .await?;
 
     let years = ride_repo.active_years(bike_id).await?;
     Ok(Json(years))
@@ -105,30 +319,15 @@ async fn get_monthly_rides(
     Path((bike_id, year)): Path<(i64, i32)>,
     Extension(session): Extension<SessionModel>,
 ) -> AppResult<Json<Vec<RideMonth>>> {
-    bike_repo.assert_owner(bike_id, session.user_id).await?;
-
-    let mut result = Vec::with_capacity(12);
-    for month in 0..12 {
-        let month = RideMonth {
-            year,
-            month: 12 - month,
-            total_distance: 0.0,
-            rides: Vec::new(),
-        };
-        result.push(month);
-    }
+    bike_repo
+        .assert_role(bike_id, session.user_id, BikeRole::Viewer)
+        .await?;
 
-    let filter = format!("{year}-");
+    let (from, to) = year_range(year)?;
     let models = ride_repo
-        .get_all_for_bike_with_date(bike_id, &filter)
+        .get_all_for_bike_between(bike_id, &from, &to, SortOrder::Desc)
         .await?;
-    for model in models {
-        let month = model.date.month() as usize;
-        result[12 - month].total_distance += model.distance;
-        result[12 - month].rides.push(model);
-    }
-
-    Ok(Json(result))
+    Ok(Json(RideMonth::group_year(year, models)))
 }
 
 async fn get_month(
@@ -137,11 +336,13 @@ async fn get_month(
     Path((bike_id, year, month)): Path<(i64, i32, i32)>,
     Extension(session): Extension<SessionModel>,
 ) -> AppResult<Json<RideMonth>> {
-    bike_repo.assert_owner(bike_id, session.user_id).await?;
+    bike_repo
+        .assert_role(bike_id, session.user_id, BikeRole::Viewer)
+        .await?;
 
-    let filter = format!("{year}-{month:02}-");
+    let (from, to) = month_range(year, month)?;
     let models = ride_repo
-        .get_all_for_bike_with_date(bike_id, &filter)
+        .get_all_for_bike_between(bike_id, &from, &to, SortOrder::Desc)
         .await?;
     let total_distance = models.iter().map(|m| m.distance).sum();
 
@@ -156,11 +357,122 @@ async fn get_month(
 async fn total_distance(
     State(bike_repo): State<BikeRepository>,
     State(ride_repo): State<RideRepository>,
-    Path((bike_id, year)): Path<(i64, u32)>,
+    Path((bike_id, year)): Path<(i64, i32)>,
     Extension(session): Extension<SessionModel>,
 ) -> AppResult<Json<f64>> {
-    bike_repo.assert_owner(bike_id, session.user_id).await?;
+    bike_repo
+        .assert_role(bike_id, session.user_id, BikeRole::Viewer)
+        .await?;
 
-    let total = ride_repo.total_distance(bike_id, year).await?;
+    let (from, to) = year_range(year)?;
+    let total = ride_repo.total_distance(bike_id, &from, &to).await?;
     Ok(Json(total))
 }
+
+async fn get_tags(
+    State(bike_repo): State<BikeRepository>,
+    State(ride_repo): State<RideRepository>,
+    Path((bike_id, ride_id)): Path<(i64, i64)>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<Json<Vec<String>>> {
+    bike_repo
+        .assert_role(bike_id, session.user_id, BikeRole::Viewer)
+        .await?;
+
+    let ride = ride_repo.get_one_for_bike(bike_id, ride_id).await?;
+    let tags = ride_repo.get_tags(ride.id).await?;
+    Ok(Json(tags))
+}
+
+/// Tag changes are versioned like other edits of the ride and require `If-Match`.
+async fn set_tags(
+    State(bike_repo): State<BikeRepository>,
+    State(ride_repo): State<RideRepository>,
+    State(pool): State<SqlitePool>,
+    Path((bike_id, ride_id)): Path<(i64, i64)>,
+    Extension(session): Extension<SessionModel>,
+    headers: HeaderMap,
+    Json(payload): Json<Vec<String>>,
+) -> AppResult<(HeaderMap, Json<Vec<String>>)> {
+    let role = bike_repo
+        .assert_role(bike_id, session.user_id, BikeRole::Rider)
+        .await?;
+
+    let expected = if_match(&headers)?;
+    let before = ride_repo.get_one_for_bike(bike_id, ride_id).await?;
+    role.assert_can_edit_ride(&before, session.user_id)?;
+    assert_version(expected, before.version, &before)?;
+    let mut tx = pool.begin().await?;
+    let before_tags = RideRepository::get_tags_in(&mut tx, ride_id).await?;
+    let (model, tags) =
+        RideRepository::set_tags_in(&mut tx, ride_id, before.version, &payload).await?;
+    HistoryRepository::record_in(
+        &mut tx,
+        HistoryEntity::Ride,
+        ride_id,
+        HistoryAction::Update,
+        Actor::User(session.user_id),
+        Some(&TaggedRide {
+            ride: &before,
+            tags: &before_tags,
+        }),
+        Some(&TaggedRide {
+            ride: &model,
+            tags: &tags,
+        }),
+    )
+    .await?;
+    tx.commit().await?;
+    Ok((etag_headers(model.version), Json(tags)))
+}
+
+fn idempotency_key(headers: &HeaderMap) -> AppResult<Option<&str>> {
+    let Some(key) = headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+
+    let key = key
+        .to_str()
+        .map_err(|_| AppError::BadRequest(String::from("Invalid idempotency key")))?;
+    valid_idempotency_key(key).map(Some)
+}
+
+pub fn valid_idempotency_key(key: &str) -> AppResult<&str> {
+    let key = key.trim();
+    if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LENGTH {
+        return Err(AppError::BadRequest(String::from(
+            "Invalid idempotency key",
+        )));
+    }
+
+    Ok(key)
+}
+
+pub fn cursor_headers(next_cursor: Option<&RideCursor>) -> AppResult<HeaderMap> {
+    let mut headers = HeaderMap::new();
+    if let Some(cursor) = next_cursor {
+        headers.insert(
+            NEXT_CURSOR_HEADER,
+            cursor.encode().parse().map_err(anyhow::Error::from)?,
+        );
+    }
+    Ok(headers)
+}
+
+pub fn year_range(year: i32) -> AppResult<(NaiveDate, NaiveDate)> {
+    let from = NaiveDate::from_ymd_opt(year, 1, 1);
+    let to = NaiveDate::from_ymd_opt(year, 12, 31);
+    from.zip(to)
+        .ok_or_else(|| AppError::BadRequest(format!("Invalid year {year}")))
+}
+
+pub fn month_range(year: i32, month: i32) -> AppResult<(NaiveDate, NaiveDate)> {
+    let invalid = || AppError::BadRequest(format!("Invalid month {year}-{month:02}"));
+    let month = u32::try_from(month).map_err(|_| invalid())?;
+    let from = NaiveDate::from_ymd_opt(year, month, 1).ok_or_else(invalid)?;
+    let to = from
+        .checked_add_months(chrono::Months::new(1))
+        .and_then(|next| next.pred_opt())
+        .ok_or_else(invalid)?;
+    Ok((from, to))
+}
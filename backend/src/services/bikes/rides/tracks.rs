@@ -0,0 +1,5 @@
+pub mod export;
+pub mod models;
+pub mod parsing;
+pub mod repository;
+pub mod routes;
@@ -0,0 +1,65 @@
+use std::io::{Cursor, Write};
+
+use quick_xml::escape::escape;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::services::bikes::rides::models::RideModel;
+use crate::utility::db_format::format_date;
+
+use super::models::TrackModel;
+
+/// Writes the ride and its stored track as a GPX 1.1 document.
+pub fn write_gpx(ride: &RideModel, bike_name: &str, track: &TrackModel) -> String {
+    let name = ride
+        .description
+        .clone()
+        .unwrap_or_else(|| format!("Ride {}", format_date(&ride.date)));
+    let name = escape(name.as_str());
+    let bike_name = escape(bike_name);
+    let time = format!("{}T00:00:00Z", format_date(&ride.date));
+
+    let mut gpx = String::new();
+    gpx.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    gpx.push_str("<gpx version=\"1.1\" creator=\"bike-service\" xmlns=\"http://www.topografix.com/GPX/1/1\">\n");
+    gpx.push_str("  <metadata>\n");
+    gpx.push_str(&format!("    <name>{name}</name>\n"));
+    gpx.push_str(&format!("    <desc>{bike_name}</desc>\n"));
+    gpx.push_str(&format!("    <time>{time}</time>\n"));
+    gpx.push_str(&format!(
+        "    <bounds minlat=\"{}\" minlon=\"{}\" maxlat=\"{}\" maxlon=\"{}\"/>\n",
+        track.bbox.min_lat, track.bbox.min_lon, track.bbox.max_lat, track.bbox.max_lon
+    ));
+    gpx.push_str("  </metadata>\n");
+    gpx.push_str("  <trk>\n");
+    gpx.push_str(&format!("    <name>{name}</name>\n"));
+    gpx.push_str(&format!("    <src>{bike_name}</src>\n"));
+    gpx.push_str("    <type>cycling</type>\n");
+    gpx.push_str("    <trkseg>\n");
+    for point in &track.points {
+        gpx.push_str(&format!(
+            "      <trkpt lat=\"{}\" lon=\"{}\"/>\n",
+            point.lat, point.lon
+        ));
+    }
+    gpx.push_str("    </trkseg>\n");
+    gpx.push_str("  </trk>\n");
+    gpx.push_str("</gpx>\n");
+    gpx
+}
+
+pub fn gpx_file_name(ride: &RideModel) -> String {
+    format!("{}-ride-{}.gpx", format_date(&ride.date), ride.id)
+}
+
+/// Packs the GPX files into a single zip archive.
+pub fn write_zip(files: &[(String, String)]) -> anyhow::Result<Vec<u8>> {
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, content) in files {
+        zip.start_file(name.as_str(), options)?;
+        zip.write_all(content.as_bytes())?;
+    }
+
+    Ok(zip.finish()?.into_inner())
+}
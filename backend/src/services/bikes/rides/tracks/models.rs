@@ -0,0 +1,142 @@
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::services::bikes::rides::models::RideModel;
+use crate::utility::{
+    db_extensions::Model,
+    geometry::{decode_polyline, BoundingBox, Point},
+};
+
+#[derive(Debug, Clone)]
+pub struct TrackRaw {
+    pub ride_id: i64,
+    pub polyline: String,
+    pub source: String,
+    pub min_lat: f64,
+    pub min_lon: f64,
+    pub max_lat: f64,
+    pub max_lon: f64,
+}
+
+/// Number and last change of the tracks a user can see, changes with every track,
+/// ride, tag, bike or membership change that may alter their heatmap.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackVersion {
+    pub tracks: i64,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TrackSource {
+    Gpx,
+    Fit,
+    Strava,
+}
+
+impl TrackSource {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            TrackSource::Gpx => "gpx",
+            TrackSource::Fit => "fit",
+            TrackSource::Strava => "strava",
+        }
+    }
+}
+
+impl FromStr for TrackSource {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "gpx" => Ok(TrackSource::Gpx),
+            "fit" => Ok(TrackSource::Fit),
+            "strava" => Ok(TrackSource::Strava),
+            _ => Err(anyhow::anyhow!("Unknown track source '{s}'")),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TrackModel {
+    pub ride_id: i64,
+    pub source: TrackSource,
+    pub bbox: BoundingBox,
+    pub points: Vec<Point>,
+}
+
+impl Model<TrackRaw> for TrackModel {}
+
+impl TryFrom<TrackRaw> for TrackModel {
+    type Error = anyhow::Error;
+    fn try_from(raw: TrackRaw) -> Result<Self, Self::Error> {
+        let source = raw.source.parse()?;
+        let points = decode_polyline(&raw.polyline)?;
+        Ok(TrackModel {
+            ride_id: raw.ride_id,
+            source,
+            bbox: BoundingBox {
+                min_lat: raw.min_lat,
+                min_lon: raw.min_lon,
+                max_lat: raw.max_lat,
+                max_lon: raw.max_lon,
+            },
+            points,
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RouteFeature {
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub bbox: [f64; 4],
+    pub geometry: LineString,
+    pub properties: RouteProperties,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LineString {
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub coordinates: Vec<[f64; 2]>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteProperties {
+    pub ride_id: i64,
+    pub bike_id: i64,
+    pub date: NaiveDate,
+    pub distance: f64,
+    pub description: Option<String>,
+    pub source: TrackSource,
+}
+
+impl RouteFeature {
+    pub fn new(ride: &RideModel, track: &TrackModel) -> Self {
+        RouteFeature {
+            kind: "Feature",
+            bbox: track.bbox.to_geojson(),
+            geometry: LineString {
+                kind: "LineString",
+                coordinates: track.points.iter().map(|p| [p.lon, p.lat]).collect(),
+            },
+            properties: RouteProperties {
+                ride_id: track.ride_id,
+                bike_id: ride.bike_id,
+                date: ride.date,
+                distance: ride.distance,
+                description: ride.description.clone(),
+                source: track.source,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportRange {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
@@ -0,0 +1,89 @@
+use anyhow::{anyhow, Context};
+use fitparser::profile::MesgNum;
+use fitparser::Value;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use crate::utility::geometry::Point;
+
+use super::models::TrackSource;
+
+const FIT_SIGNATURE: &[u8] = b".FIT";
+const SEMICIRCLES_TO_DEGREES: f64 = 180.0 / 2_147_483_648.0;
+
+/// Reads the track points from an uploaded GPX or FIT file.
+pub fn parse_track(content: &[u8]) -> anyhow::Result<(TrackSource, Vec<Point>)> {
+    if content.get(8..12) == Some(FIT_SIGNATURE) {
+        Ok((TrackSource::Fit, parse_fit(content)?))
+    } else {
+        Ok((TrackSource::Gpx, parse_gpx(content)?))
+    }
+}
+
+fn parse_gpx(content: &[u8]) -> anyhow::Result<Vec<Point>> {
+    let mut reader = Reader::from_reader(content);
+    reader.config_mut().trim_text(true);
+
+    let mut points = Vec::new();
+    loop {
+        match reader.read_event()? {
+            Event::Start(element) | Event::Empty(element) => {
+                let name = element.local_name();
+                if name.as_ref() == b"trkpt" || name.as_ref() == b"rtept" {
+                    points.push(parse_gpx_point(&element)?);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(points)
+}
+
+fn parse_gpx_point(element: &BytesStart) -> anyhow::Result<Point> {
+    let coordinate = |name: &str| -> anyhow::Result<f64> {
+        let attribute = element
+            .try_get_attribute(name)?
+            .ok_or_else(|| anyhow!("Track point is missing '{name}'"))?;
+        let value = attribute.unescape_value()?;
+        value
+            .trim()
+            .parse::<f64>()
+            .with_context(|| format!("Invalid '{name}' value '{value}'"))
+    };
+
+    let point = Point::new(coordinate("lat")?, coordinate("lon")?);
+    if !point.is_valid() {
+        return Err(anyhow!("Track point {point:?} is out of range"));
+    }
+
+    Ok(point)
+}
+
+fn parse_fit(content: &[u8]) -> anyhow::Result<Vec<Point>> {
+    let records = fitparser::from_bytes(content)?;
+    let points = records
+        .iter()
+        .filter(|record| record.kind() == MesgNum::Record)
+        .filter_map(|record| {
+            let mut lat = None;
+            let mut lon = None;
+            for field in record.fields() {
+                match (field.name(), field.value()) {
+                    ("position_lat", Value::SInt32(v)) => lat = Some(*v),
+                    ("position_long", Value::SInt32(v)) => lon = Some(*v),
+                    _ => {}
+                }
+            }
+
+            let point = Point::new(
+                f64::from(lat?) * SEMICIRCLES_TO_DEGREES,
+                f64::from(lon?) * SEMICIRCLES_TO_DEGREES,
+            );
+            point.is_valid().then_some(point)
+        })
+        .collect();
+
+    Ok(points)
+}
@@ -0,0 +1,144 @@
+use chrono::NaiveDate;
+use sqlx::SqlitePool;
+
+use crate::utility::{
+    db_extensions::IntoModels,
+    db_format::format_date,
+    error::{AppError, AppResult},
+    geometry::{encode_polyline, simplify, BoundingBox, Point},
+};
+
+use super::models::{TrackModel, TrackRaw, TrackSource, TrackVersion};
+
+/// Douglas–Peucker tolerance in degrees (roughly 2 meters).
+const SIMPLIFY_TOLERANCE: f64 = 0.00002;
+
+#[derive(Clone)]
+pub struct TrackRepository(SqlitePool);
+
+impl TrackRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self(pool)
+    }
+
+    pub async fn try_get(&self, ride_id: i64) -> AppResult<Option<TrackModel>> {
+        let model = sqlx::query_as!(
+            TrackRaw,
+            "SELECT ride_id, polyline, source, min_lat, min_lon, max_lat, max_lon FROM ride_tracks WHERE ride_id = ?",
+            ride_id
+        )
+        .fetch_optional(&self.0)
+        .await?
+        .map(TrackModel::try_from)
+        .transpose()?;
+
+        Ok(model)
+    }
+
+    /// Changes whenever the result of `get_all_in_bounds` may change for the user.
+    pub async fn get_version(&self, user_id: i64) -> AppResult<TrackVersion> {
+        let version = sqlx::query_as!(
+            TrackVersion,
+            r#"SELECT COUNT(*) AS "tracks!: i64", MAX(MAX(t.updated_at), MAX(r.updated_at), MAX(b.updated_at), COALESCE((SELECT MAX(changed_at) FROM bike_member_changes WHERE user_id = ?1), '')) AS "updated_at: String"
+            FROM ride_tracks t
+            JOIN rides r ON r.id = t.ride_id
+            JOIN bikes b ON b.id = r.bike_id
+            WHERE b.id IN (SELECT bike_id FROM bike_members WHERE user_id = ?1)"#,
+            user_id
+        )
+        .fetch_one(&self.0)
+        .await?;
+
+        Ok(version)
+    }
+
+    pub async fn get_all_in_bounds(
+        &self,
+        user_id: i64,
+        bounds: &BoundingBox,
+        bike_id: Option<i64>,
+        range: Option<(NaiveDate, NaiveDate)>,
+        tag: Option<String>,
+    ) -> AppResult<Vec<TrackModel>> {
+        let (from, to) = match range {
+            Some((from, to)) => (format_date(&from), format_date(&to)),
+            None => (String::from("0000-01-01"), String::from("9999-12-31")),
+        };
+        let models = sqlx::query_as!(
+            TrackRaw,
+            "SELECT t.ride_id, t.polyline, t.source, t.min_lat, t.min_lon, t.max_lat, t.max_lon FROM ride_tracks t
+            JOIN rides r ON r.id = t.ride_id
+            JOIN bikes b ON b.id = r.bike_id
+            WHERE b.id IN (SELECT bike_id FROM bike_members WHERE user_id = ?1) AND b.deleted_at IS NULL AND r.deleted_at IS NULL
+            AND (?2 IS NULL OR r.bike_id = ?2) AND r.date >= ?3 AND r.date <= ?4
+            AND (?5 IS NULL OR EXISTS (SELECT 1 FROM ride_tags rt WHERE rt.ride_id = r.id AND rt.tag = ?5))
+            AND t.max_lat >= ?6 AND t.min_lat <= ?7 AND t.max_lon >= ?8 AND t.min_lon <= ?9
+            ORDER BY t.ride_id",
+            user_id,
+            bike_id,
+            from,
+            to,
+            tag,
+            bounds.min_lat,
+            bounds.max_lat,
+            bounds.min_lon,
+            bounds.max_lon
+        )
+        .fetch_all(&self.0)
+        .await?
+        .into_models()?;
+
+        Ok(models)
+    }
+
+    pub async fn save(
+        &self,
+        ride_id: i64,
+        source: TrackSource,
+        points: &[Point],
+    ) -> AppResult<TrackModel> {
+        let points = simplify(points, SIMPLIFY_TOLERANCE);
+        let bbox = BoundingBox::from_points(&points)
+            .ok_or_else(|| AppError::BadRequest("Track has no points".to_string()))?;
+        let polyline = encode_polyline(&points);
+        let source_str = source.as_str();
+
+        sqlx::query!(
+            "INSERT INTO ride_tracks (ride_id, polyline, source, min_lat, min_lon, max_lat, max_lon) VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (ride_id) DO UPDATE SET polyline = excluded.polyline, source = excluded.source, min_lat = excluded.min_lat, min_lon = excluded.min_lon, max_lat = excluded.max_lat, max_lon = excluded.max_lon",
+            ride_id,
+            polyline,
+            source_str,
+            bbox.min_lat,
+            bbox.min_lon,
+            bbox.max_lat,
+            bbox.max_lon
+        )
+        .execute(&self.0)
+        .await?;
+
+        let model = TrackModel {
+            ride_id,
+            source,
+            bbox,
+            points,
+        };
+
+        Ok(model)
+    }
+
+    pub async fn delete(&self, ride_id: i64) -> AppResult<()> {
+        let affected = sqlx::query!("DELETE FROM ride_tracks WHERE ride_id = ?", ride_id)
+            .execute(&self.0)
+            .await?
+            .rows_affected();
+
+        if affected == 0 {
+            return Err(AppError::NotFound(format!(
+                "No track found for ride {ride_id}",
+            )));
+        }
+
+        Ok(())
+    }
+}
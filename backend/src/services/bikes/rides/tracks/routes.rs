@@ -0,0 +1,176 @@
+use axum::extract::{DefaultBodyLimit, Multipart, Path, Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::routing::{delete, get, put};
+use axum::{Extension, Json, Router};
+
+use crate::services::auth::models::SessionModel;
+use crate::services::bikes::members::models::BikeRole;
+use crate::services::bikes::repository::BikeRepository;
+use crate::services::bikes::rides::models::SortOrder;
+use crate::services::bikes::rides::repository::RideRepository;
+use crate::utility::error::{AppError, AppResult};
+use crate::utility::state::AppState;
+
+use super::export::{gpx_file_name, write_gpx, write_zip};
+use super::models::{ExportRange, RouteFeature};
+use super::parsing::parse_track;
+use super::repository::TrackRepository;
+
+const TRACK_FILE_LIMIT: usize = 32 * 1024 * 1024;
+
+pub fn router_with_auth() -> Router<AppState> {
+    Router::new()
+        .route("/{id}/route.geojson", get(get_route))
+        .route(
+            "/{id}/track",
+            put(upload_track).layer(DefaultBodyLimit::max(TRACK_FILE_LIMIT)),
+        )
+        .route("/{id}/track", delete(delete_track))
+        .route("/{id}/export.gpx", get(export_gpx))
+        .route("/export.zip", get(export_zip))
+}
+
+async fn get_route(
+    State(bike_repo): State<BikeRepository>,
+    State(ride_repo): State<RideRepository>,
+    State(track_repo): State<TrackRepository>,
+    Path((bike_id, ride_id)): Path<(i64, i64)>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<(HeaderMap, Json<RouteFeature>)> {
+    bike_repo
+        .assert_role(bike_id, session.user_id, BikeRole::Viewer)
+        .await?;
+
+    let ride = ride_repo.get_one_for_bike(bike_id, ride_id).await?;
+    let track = track_repo
+        .try_get(ride.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("No track found for ride {ride_id}")))?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        "application/geo+json"
+            .parse()
+            .map_err(anyhow::Error::from)?,
+    );
+
+    Ok((headers, Json(RouteFeature::new(&ride, &track))))
+}
+
+async fn upload_track(
+    State(bike_repo): State<BikeRepository>,
+    State(ride_repo): State<RideRepository>,
+    State(track_repo): State<TrackRepository>,
+    Path((bike_id, ride_id)): Path<(i64, i64)>,
+    Extension(session): Extension<SessionModel>,
+    mut multipart: Multipart,
+) -> AppResult<Json<RouteFeature>> {
+    let role = bike_repo
+        .assert_role(bike_id, session.user_id, BikeRole::Rider)
+        .await?;
+
+    let ride = ride_repo.get_one_for_bike(bike_id, ride_id).await?;
+    role.assert_can_edit_ride(&ride, session.user_id)?;
+
+    let mut file_content = None;
+    while let Some(field) = multipart.next_field().await? {
+        if let Some("track-file") = field.name() {
+            file_content = Some(field.bytes().await?.to_vec());
+            break;
+        }
+    }
+
+    let content =
+        file_content.ok_or_else(|| AppError::BadRequest(String::from("No file provided")))?;
+    let (source, points) =
+        parse_track(&content).map_err(|e| AppError::BadRequest(format!("Invalid track: {e}")))?;
+
+    let track = track_repo.save(ride.id, source, &points).await?;
+    Ok(Json(RouteFeature::new(&ride, &track)))
+}
+
+async fn delete_track(
+    State(bike_repo): State<BikeRepository>,
+    State(ride_repo): State<RideRepository>,
+    State(track_repo): State<TrackRepository>,
+    Path((bike_id, ride_id)): Path<(i64, i64)>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<StatusCode> {
+    let role = bike_repo
+        .assert_role(bike_id, session.user_id, BikeRole::Rider)
+        .await?;
+
+    let ride = ride_repo.get_one_for_bike(bike_id, ride_id).await?;
+    role.assert_can_edit_ride(&ride, session.user_id)?;
+    track_repo.delete(ride.id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn export_gpx(
+    State(bike_repo): State<BikeRepository>,
+    State(ride_repo): State<RideRepository>,
+    State(track_repo): State<TrackRepository>,
+    Path((bike_id, ride_id)): Path<(i64, i64)>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<(HeaderMap, String)> {
+    bike_repo
+        .assert_role(bike_id, session.user_id, BikeRole::Viewer)
+        .await?;
+
+    let bike = bike_repo.get_one(bike_id).await?;
+    let ride = ride_repo.get_one_for_bike(bike_id, ride_id).await?;
+    let track = track_repo
+        .try_get(ride.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("No track found for ride {ride_id}")))?;
+
+    let data = write_gpx(&ride, &bike.name, &track);
+    let headers = attachment_headers("application/gpx+xml", &gpx_file_name(&ride))?;
+    Ok((headers, data))
+}
+
+async fn export_zip(
+    State(bike_repo): State<BikeRepository>,
+    State(ride_repo): State<RideRepository>,
+    State(track_repo): State<TrackRepository>,
+    Path(bike_id): Path<i64>,
+    Query(range): Query<ExportRange>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<(HeaderMap, Vec<u8>)> {
+    bike_repo
+        .assert_role(bike_id, session.user_id, BikeRole::Viewer)
+        .await?;
+
+    let bike = bike_repo.get_one(bike_id).await?;
+    let rides = ride_repo
+        .get_all_for_bike_between(bike_id, &range.from, &range.to, SortOrder::Asc)
+        .await?;
+
+    let mut files = Vec::new();
+    for ride in rides {
+        if let Some(track) = track_repo.try_get(ride.id).await? {
+            files.push((gpx_file_name(&ride), write_gpx(&ride, &bike.name, &track)));
+        }
+    }
+
+    let data = write_zip(&files)?;
+    let file_name = format!("rides-{}-{}.zip", range.from, range.to);
+    let headers = attachment_headers("application/zip", &file_name)?;
+    Ok((headers, data))
+}
+
+fn attachment_headers(content_type: &str, file_name: &str) -> AppResult<HeaderMap> {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        content_type.parse().map_err(anyhow::Error::from)?,
+    );
+    headers.insert(
+        header::CONTENT_DISPOSITION,
+        format!("attachment; filename=\"{file_name}\"")
+            .parse()
+            .map_err(anyhow::Error::from)?,
+    );
+    Ok(headers)
+}
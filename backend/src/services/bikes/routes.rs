@@ -1,40 +1,127 @@
-use axum::extract::{Path, State};
-use axum::http::StatusCode;
-use axum::routing::{delete, get, post, put};
+use std::collections::HashSet;
+
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::{delete, get, patch, post, put};
 use axum::{Extension, Json, Router};
+use chrono::{NaiveDateTime, Utc};
+use sqlx::SqlitePool;
 
 use crate::services::auth::models::SessionModel;
-use crate::utility::error::AppResult;
+use crate::services::bikes::members::models::BikeRole;
+use crate::services::components::models::ComponentModel;
+use crate::services::components::repository::ComponentRepository;
+use crate::services::history::models::{Actor, HistoryAction, HistoryEntity, HistoryModel};
+use crate::services::history::repository::HistoryRepository;
+use crate::utility::error::{AppError, AppResult};
 use crate::utility::state::AppState;
+use crate::utility::versioning::{assert_version, etag_headers, if_match};
 
-use super::models::{BikeModel, BikePartial};
+use super::maintenance;
+use super::maintenance::repository::MaintenanceRepository;
+use super::members;
+use super::members::models::{BikeOrder, RiderTotals};
+use super::members::repository::MemberRepository;
+use super::models::{
+    BikeCosts, BikeListQuery, BikeModel, BikePartial, BikePatch, BikeWithOdometer, Odometer,
+    TimelineEvent, TimelineRange,
+};
+use super::photos;
 use super::repository::BikeRepository;
 use super::rides;
+use super::rides::models::SortOrder;
+use super::rides::repository::RideRepository;
 
 pub fn router_with_auth() -> Router<AppState> {
     Router::new()
         .nest("/{id}/rides", rides::routes::router_with_auth())
+        .nest("/{id}/maintenance", maintenance::routes::router_with_auth())
+        .nest("/{id}/photo", photos::routes::router_with_auth())
+        .nest("/{id}/members", members::routes::router_with_auth())
         .route("/", get(get_all_bikes))
         .route("/", post(create_bike))
+        .route("/costs", get(get_all_costs))
+        .route("/order", put(reorder_bikes))
         .route("/{id}", get(get_bike))
         .route("/{id}", put(update_bike))
+        .route("/{id}", patch(patch_bike))
         .route("/{id}", delete(delete_bike))
+        .route("/{id}/history", get(get_history))
+        .route("/{id}/components", get(get_components))
+        .route("/{id}/timeline", get(get_timeline))
+        .route("/{id}/odometer", get(get_odometer))
+        .route("/{id}/costs", get(get_costs))
+        .route("/{id}/riders", get(get_rider_totals))
+        .route("/{id}/retire", post(retire_bike))
+        .route("/{id}/unretire", post(unretire_bike))
 }
 
 async fn get_all_bikes(
     State(repo): State<BikeRepository>,
+    State(member_repo): State<MemberRepository>,
+    Query(query): Query<BikeListQuery>,
     Extension(session): Extension<SessionModel>,
-) -> AppResult<Json<Vec<BikeModel>>> {
+) -> AppResult<Json<Vec<BikeWithOdometer>>> {
     let models = repo.get_all(session.user_id).await?;
+    let distances = repo.get_rides_distances(session.user_id).await?;
+    let mut memberships = member_repo.get_memberships(session.user_id).await?;
+    let models = models
+        .into_iter()
+        .filter(|bike| query.retired || bike.retired_at.is_none())
+        .filter_map(|bike| {
+            let rides_distance = distances.get(&bike.id).copied().unwrap_or_default();
+            let odometer = Odometer::new(bike.initial_distance, rides_distance);
+            let membership = memberships.remove(&bike.id)?;
+            Some(BikeWithOdometer {
+                bike,
+                odometer,
+                role: membership.role,
+                sort_order: membership.sort_order,
+                group: membership.group,
+            })
+        })
+        .collect();
     Ok(Json(models))
 }
 
+/// Arranges the list of bikes of the user, optionally moving them into groups.
+async fn reorder_bikes(
+    State(member_repo): State<MemberRepository>,
+    Extension(session): Extension<SessionModel>,
+    Json(payload): Json<Vec<BikeOrder>>,
+) -> AppResult<StatusCode> {
+    let unique = payload
+        .iter()
+        .map(|entry| entry.bike_id)
+        .collect::<HashSet<_>>();
+    if unique.len() != payload.len() {
+        return Err(AppError::BadRequest(String::from(
+            "Each bike can appear only once in the order",
+        )));
+    }
+
+    member_repo.reorder(session.user_id, &payload).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
 async fn create_bike(
-    State(bike_repo): State<BikeRepository>,
+    State(pool): State<SqlitePool>,
     Extension(session): Extension<SessionModel>,
     Json(payload): Json<BikePartial>,
 ) -> AppResult<(StatusCode, Json<BikeModel>)> {
-    let model = bike_repo.create(session.user_id, &payload).await?;
+    let mut tx = pool.begin().await?;
+    let model = BikeRepository::create_in(&mut tx, session.user_id, &payload).await?;
+    HistoryRepository::record_in(
+        &mut tx,
+        HistoryEntity::Bike,
+        model.id,
+        HistoryAction::Create,
+        Actor::User(session.user_id),
+        None,
+        Some(&model),
+    )
+    .await?;
+    tx.commit().await?;
     Ok((StatusCode::CREATED, Json(model)))
 }
 
@@ -42,32 +129,295 @@ async fn get_bike(
     State(bike_repo): State<BikeRepository>,
     Path(id): Path<i64>,
     Extension(session): Extension<SessionModel>,
-) -> AppResult<Json<BikeModel>> {
-    bike_repo.assert_owner(id, session.user_id).await?;
+) -> AppResult<(HeaderMap, Json<BikeModel>)> {
+    bike_repo
+        .assert_role(id, session.user_id, BikeRole::Viewer)
+        .await?;
 
     let model = bike_repo.get_one(id).await?;
-    Ok(Json(model))
+    Ok((etag_headers(model.version), Json(model)))
 }
 
 async fn update_bike(
     State(bike_repo): State<BikeRepository>,
+    State(pool): State<SqlitePool>,
     Path(id): Path<i64>,
     Extension(session): Extension<SessionModel>,
+    headers: HeaderMap,
     Json(payload): Json<BikePartial>,
-) -> AppResult<Json<BikeModel>> {
-    bike_repo.assert_owner(id, session.user_id).await?;
+) -> AppResult<(HeaderMap, Json<BikeModel>)> {
+    bike_repo
+        .assert_role(id, session.user_id, BikeRole::Owner)
+        .await?;
 
-    let model = bike_repo.update(id, session.user_id, &payload).await?;
-    Ok(Json(model))
+    let expected = if_match(&headers)?;
+    let before = bike_repo.get_one(id).await?;
+    assert_version(expected, before.version, &before)?;
+    save_update(&pool, &session, before, &payload).await
+}
+
+async fn patch_bike(
+    State(bike_repo): State<BikeRepository>,
+    State(pool): State<SqlitePool>,
+    Path(id): Path<i64>,
+    Extension(session): Extension<SessionModel>,
+    headers: HeaderMap,
+    Json(patch): Json<BikePatch>,
+) -> AppResult<(HeaderMap, Json<BikeModel>)> {
+    bike_repo
+        .assert_role(id, session.user_id, BikeRole::Owner)
+        .await?;
+
+    let expected = if_match(&headers)?;
+    let before = bike_repo.get_one(id).await?;
+    assert_version(expected, before.version, &before)?;
+    let payload = patch.apply(&before);
+    save_update(&pool, &session, before, &payload).await
+}
+
+async fn save_update(
+    pool: &SqlitePool,
+    session: &SessionModel,
+    before: BikeModel,
+    payload: &BikePartial,
+) -> AppResult<(HeaderMap, Json<BikeModel>)> {
+    let mut tx = pool.begin().await?;
+    let model = BikeRepository::update_in(&mut tx, before.id, payload, before.version).await?;
+    HistoryRepository::record_in(
+        &mut tx,
+        HistoryEntity::Bike,
+        before.id,
+        HistoryAction::Update,
+        Actor::User(session.user_id),
+        Some(&before),
+        Some(&model),
+    )
+    .await?;
+    tx.commit().await?;
+    Ok((etag_headers(model.version), Json(model)))
 }
 
 async fn delete_bike(
     State(bike_repo): State<BikeRepository>,
+    State(pool): State<SqlitePool>,
     Path(id): Path<i64>,
     Extension(session): Extension<SessionModel>,
+    headers: HeaderMap,
 ) -> AppResult<StatusCode> {
-    bike_repo.assert_owner(id, session.user_id).await?;
+    bike_repo
+        .assert_role(id, session.user_id, BikeRole::Owner)
+        .await?;
+
+    let expected = if_match(&headers)?;
+    let before = bike_repo.get_one(id).await?;
+    assert_version(expected, before.version, &before)?;
+    let mut tx = pool.begin().await?;
+    let rides = BikeRepository::delete_in(&mut tx, id, before.version).await?;
 
-    bike_repo.delete(id).await?;
+    let actor = Actor::User(session.user_id);
+    HistoryRepository::record_in(
+        &mut tx,
+        HistoryEntity::Bike,
+        id,
+        HistoryAction::Delete,
+        actor,
+        Some(&before),
+        None,
+    )
+    .await?;
+    for before in rides {
+        HistoryRepository::record_in(
+            &mut tx,
+            HistoryEntity::Ride,
+            before.id,
+            HistoryAction::Delete,
+            actor,
+            Some(&before),
+            None,
+        )
+        .await?;
+    }
+    tx.commit().await?;
     Ok(StatusCode::NO_CONTENT)
 }
+
+async fn get_history(
+    State(bike_repo): State<BikeRepository>,
+    State(history_repo): State<HistoryRepository>,
+    Path(id): Path<i64>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<Json<Vec<HistoryModel>>> {
+    // History stays available after the bike is deleted
+    bike_repo
+        .assert_role_including_deleted(id, session.user_id, BikeRole::Viewer)
+        .await?;
+
+    let history = history_repo.get_all(HistoryEntity::Bike, id).await?;
+    Ok(Json(history))
+}
+
+async fn get_components(
+    State(bike_repo): State<BikeRepository>,
+    State(component_repo): State<ComponentRepository>,
+    Path(id): Path<i64>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<Json<Vec<ComponentModel>>> {
+    bike_repo
+        .assert_role(id, session.user_id, BikeRole::Viewer)
+        .await?;
+
+    let models = component_repo.get_all_for_bike(id).await?;
+    Ok(Json(models))
+}
+
+/// Rides and maintenance of the bike in the range, newest first.
+async fn get_timeline(
+    State(bike_repo): State<BikeRepository>,
+    State(ride_repo): State<RideRepository>,
+    State(maintenance_repo): State<MaintenanceRepository>,
+    Path(id): Path<i64>,
+    Query(range): Query<TimelineRange>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<Json<Vec<TimelineEvent>>> {
+    bike_repo
+        .assert_role(id, session.user_id, BikeRole::Viewer)
+        .await?;
+
+    let rides = ride_repo
+        .get_all_for_bike_between(id, &range.from, &range.to, SortOrder::Desc)
+        .await?;
+    let maintenance = maintenance_repo
+        .get_all_for_bike_between(id, &range.from, &range.to)
+        .await?;
+
+    let mut events = rides
+        .into_iter()
+        .map(TimelineEvent::Ride)
+        .chain(maintenance.into_iter().map(TimelineEvent::Maintenance))
+        .collect::<Vec<_>>();
+    events.sort_by_key(|event| std::cmp::Reverse(event.date()));
+    Ok(Json(events))
+}
+
+async fn get_odometer(
+    State(bike_repo): State<BikeRepository>,
+    Path(id): Path<i64>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<Json<Odometer>> {
+    bike_repo
+        .assert_role(id, session.user_id, BikeRole::Viewer)
+        .await?;
+
+    let odometer = bike_repo.get_odometer(id).await?;
+    Ok(Json(odometer))
+}
+
+async fn get_costs(
+    State(bike_repo): State<BikeRepository>,
+    State(maintenance_repo): State<MaintenanceRepository>,
+    Path(id): Path<i64>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<Json<BikeCosts>> {
+    bike_repo
+        .assert_role(id, session.user_id, BikeRole::Viewer)
+        .await?;
+
+    let bike = bike_repo.get_one(id).await?;
+    let odometer = bike_repo.get_odometer(id).await?;
+    let maintenance_cost = maintenance_repo.get_cost_for_bike(id).await?;
+    Ok(Json(BikeCosts::new(&bike, maintenance_cost, &odometer)))
+}
+
+async fn get_rider_totals(
+    State(bike_repo): State<BikeRepository>,
+    State(member_repo): State<MemberRepository>,
+    Path(id): Path<i64>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<Json<Vec<RiderTotals>>> {
+    bike_repo
+        .assert_role(id, session.user_id, BikeRole::Viewer)
+        .await?;
+
+    let totals = member_repo.get_rider_totals(id).await?;
+    Ok(Json(totals))
+}
+
+/// Costs of all bikes, the cheapest per kilometer first.
+async fn get_all_costs(
+    State(bike_repo): State<BikeRepository>,
+    State(maintenance_repo): State<MaintenanceRepository>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<Json<Vec<BikeCosts>>> {
+    let bikes = bike_repo.get_all(session.user_id).await?;
+    let distances = bike_repo.get_rides_distances(session.user_id).await?;
+    let costs = maintenance_repo.get_costs(session.user_id).await?;
+    let mut models = bikes
+        .iter()
+        .map(|bike| {
+            let rides_distance = distances.get(&bike.id).copied().unwrap_or_default();
+            let odometer = Odometer::new(bike.initial_distance, rides_distance);
+            let maintenance_cost = costs.get(&bike.id).copied().unwrap_or_default();
+            BikeCosts::new(bike, maintenance_cost, &odometer)
+        })
+        .collect::<Vec<_>>();
+    models.sort_by(|a, b| match (a.cost_per_km, b.cost_per_km) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        (a, b) => b.is_some().cmp(&a.is_some()),
+    });
+    Ok(Json(models))
+}
+
+/// Hides the bike from selection while keeping its rides in totals and history.
+async fn retire_bike(
+    State(bike_repo): State<BikeRepository>,
+    State(pool): State<SqlitePool>,
+    Path(id): Path<i64>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<(HeaderMap, Json<BikeModel>)> {
+    bike_repo
+        .assert_role(id, session.user_id, BikeRole::Owner)
+        .await?;
+
+    let now = Utc::now().naive_utc();
+    set_retired(&pool, &session, id, Some(&now)).await
+}
+
+async fn unretire_bike(
+    State(bike_repo): State<BikeRepository>,
+    State(pool): State<SqlitePool>,
+    Path(id): Path<i64>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<(HeaderMap, Json<BikeModel>)> {
+    bike_repo
+        .assert_role(id, session.user_id, BikeRole::Owner)
+        .await?;
+
+    set_retired(&pool, &session, id, None).await
+}
+
+async fn set_retired(
+    pool: &SqlitePool,
+    session: &SessionModel,
+    bike_id: i64,
+    retired_at: Option<&NaiveDateTime>,
+) -> AppResult<(HeaderMap, Json<BikeModel>)> {
+    let mut tx = pool.begin().await?;
+    let before = BikeRepository::get_one_in(&mut tx, bike_id).await?;
+    if before.retired_at.is_some() == retired_at.is_some() {
+        return Ok((etag_headers(before.version), Json(before)));
+    }
+
+    let model = BikeRepository::set_retired_in(&mut tx, bike_id, retired_at).await?;
+    HistoryRepository::record_in(
+        &mut tx,
+        HistoryEntity::Bike,
+        bike_id,
+        HistoryAction::Update,
+        Actor::User(session.user_id),
+        Some(&before),
+        Some(&model),
+    )
+    .await?;
+    tx.commit().await?;
+    Ok((etag_headers(model.version), Json(model)))
+}
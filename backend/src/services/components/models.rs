@@ -0,0 +1,169 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::utility::{
+    db_extensions::Model,
+    db_format::{parse_date, parse_date_time},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ComponentKind {
+    Chain,
+    Cassette,
+    Chainring,
+    Tire,
+    BrakePads,
+    Other,
+}
+
+impl ComponentKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ComponentKind::Chain => "chain",
+            ComponentKind::Cassette => "cassette",
+            ComponentKind::Chainring => "chainring",
+            ComponentKind::Tire => "tire",
+            ComponentKind::BrakePads => "brakePads",
+            ComponentKind::Other => "other",
+        }
+    }
+}
+
+impl FromStr for ComponentKind {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "chain" => Ok(ComponentKind::Chain),
+            "cassette" => Ok(ComponentKind::Cassette),
+            "chainring" => Ok(ComponentKind::Chainring),
+            "tire" => Ok(ComponentKind::Tire),
+            "brakePads" => Ok(ComponentKind::BrakePads),
+            "other" => Ok(ComponentKind::Other),
+            _ => Err(anyhow!("Unknown component kind {s}")),
+        }
+    }
+}
+
+/// Component with its current installation, `distance` includes the initial distance
+/// and all rides on the bikes it was installed on.
+#[derive(Debug, Clone)]
+pub struct ComponentRaw {
+    pub id: i64,
+    pub owner_id: i64,
+    pub kind: String,
+    pub brand: Option<String>,
+    pub model: Option<String>,
+    pub initial_distance: f64,
+    pub created_at: String,
+    pub bike_id: Option<i64>,
+    pub installed_at: Option<String>,
+    pub distance: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentModel {
+    pub id: i64,
+    pub owner_id: i64,
+    pub kind: ComponentKind,
+    pub brand: Option<String>,
+    pub model: Option<String>,
+    pub initial_distance: f64,
+    pub created_at: NaiveDateTime,
+    pub bike_id: Option<i64>,
+    pub installed_at: Option<NaiveDate>,
+    pub distance: f64,
+}
+
+impl Model<ComponentRaw> for ComponentModel {}
+
+impl TryFrom<ComponentRaw> for ComponentModel {
+    type Error = anyhow::Error;
+    fn try_from(raw: ComponentRaw) -> Result<Self, Self::Error> {
+        Ok(ComponentModel {
+            id: raw.id,
+            owner_id: raw.owner_id,
+            kind: raw.kind.parse()?,
+            brand: raw.brand,
+            model: raw.model,
+            initial_distance: raw.initial_distance,
+            created_at: parse_date_time(&raw.created_at)?,
+            bike_id: raw.bike_id,
+            installed_at: raw.installed_at.map(|s| parse_date(&s)).transpose()?,
+            distance: raw.distance,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InstallationRaw {
+    pub id: i64,
+    pub component_id: i64,
+    pub bike_id: i64,
+    pub installed_at: String,
+    pub removed_at: Option<String>,
+    pub distance: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallationModel {
+    pub id: i64,
+    pub component_id: i64,
+    pub bike_id: i64,
+    pub installed_at: NaiveDate,
+    pub removed_at: Option<NaiveDate>,
+    pub distance: f64,
+}
+
+impl Model<InstallationRaw> for InstallationModel {}
+
+impl TryFrom<InstallationRaw> for InstallationModel {
+    type Error = anyhow::Error;
+    fn try_from(raw: InstallationRaw) -> Result<Self, Self::Error> {
+        Ok(InstallationModel {
+            id: raw.id,
+            component_id: raw.component_id,
+            bike_id: raw.bike_id,
+            installed_at: parse_date(&raw.installed_at)?,
+            removed_at: raw.removed_at.map(|s| parse_date(&s)).transpose()?,
+            distance: raw.distance,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentPartial {
+    pub kind: ComponentKind,
+    pub brand: Option<String>,
+    pub model: Option<String>,
+    #[serde(default)]
+    pub initial_distance: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentCreate {
+    #[serde(flatten)]
+    pub component: ComponentPartial,
+    pub install: Option<ComponentInstall>,
+}
+
+/// Installs the component on the bike, removing it from its current bike on the same date.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentInstall {
+    pub bike_id: i64,
+    pub date: NaiveDate,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentRemove {
+    pub date: NaiveDate,
+}
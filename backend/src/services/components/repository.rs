@@ -0,0 +1,307 @@
+use chrono::{NaiveDate, Utc};
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::utility::{
+    db_extensions::IntoModels,
+    db_format::{format_date, format_date_time, parse_date},
+    error::{AppError, AppResult},
+};
+
+use super::models::{
+    ComponentInstall, ComponentModel, ComponentPartial, ComponentRaw, ComponentRemove,
+    InstallationModel, InstallationRaw,
+};
+
+#[derive(Clone)]
+pub struct ComponentRepository(SqlitePool);
+
+impl ComponentRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self(pool)
+    }
+
+    pub async fn assert_owner(&self, component_id: i64, user_id: i64) -> AppResult<()> {
+        let something = sqlx::query!(
+            "SELECT id FROM components WHERE id = ? AND owner_id = ?",
+            component_id,
+            user_id
+        )
+        .fetch_optional(&self.0)
+        .await?;
+
+        match something {
+            Some(_) => Ok(()),
+            None => Err(AppError::Forbidden),
+        }
+    }
+
+    /// Components may be logged in the maintenance of bikes they are or were installed on, or of
+    /// bikes their owner is a member of.
+    pub async fn assert_usable_on_bike(&self, component_id: i64, bike_id: i64) -> AppResult<()> {
+        let something = sqlx::query!(
+            "SELECT id FROM components WHERE id = ?1 AND (EXISTS (SELECT 1 FROM component_installations WHERE component_id = ?1 AND bike_id = ?2) OR owner_id IN (SELECT user_id FROM bike_members WHERE bike_id = ?2))",
+            component_id,
+            bike_id
+        )
+        .fetch_optional(&self.0)
+        .await?;
+
+        match something {
+            Some(_) => Ok(()),
+            None => Err(AppError::Forbidden),
+        }
+    }
+
+    pub async fn get_all(&self, owner_id: i64) -> AppResult<Vec<ComponentModel>> {
+        // Current installation and the distance of the rides made on the bikes while the
+        // component was installed
+        let models = sqlx::query_as!(
+            ComponentRaw,
+            r#"SELECT components.id AS "id!", components.owner_id AS "owner_id!", components.kind AS "kind!", components.brand, components.model, components.initial_distance AS "initial_distance!", components.created_at AS "created_at!", current.bike_id AS "bike_id?", current.installed_at AS "installed_at?", components.initial_distance + COALESCE((SELECT SUM(rides.distance) FROM component_installations AS past JOIN rides ON rides.bike_id = past.bike_id AND rides.date >= past.installed_at AND (past.removed_at IS NULL OR rides.date < past.removed_at) WHERE past.component_id = components.id AND rides.deleted_at IS NULL), 0.0) AS "distance!: f64" FROM components LEFT JOIN component_installations AS current ON current.component_id = components.id AND current.removed_at IS NULL WHERE components.owner_id = ? ORDER BY components.id"#,
+            owner_id
+        )
+        .fetch_all(&self.0)
+        .await?
+        .into_models()?;
+
+        Ok(models)
+    }
+
+    pub async fn get_all_for_bike(&self, bike_id: i64) -> AppResult<Vec<ComponentModel>> {
+        let models = sqlx::query_as!(
+            ComponentRaw,
+            r#"SELECT components.id AS "id!", components.owner_id AS "owner_id!", components.kind AS "kind!", components.brand, components.model, components.initial_distance AS "initial_distance!", components.created_at AS "created_at!", current.bike_id AS "bike_id?", current.installed_at AS "installed_at?", components.initial_distance + COALESCE((SELECT SUM(rides.distance) FROM component_installations AS past JOIN rides ON rides.bike_id = past.bike_id AND rides.date >= past.installed_at AND (past.removed_at IS NULL OR rides.date < past.removed_at) WHERE past.component_id = components.id AND rides.deleted_at IS NULL), 0.0) AS "distance!: f64" FROM components LEFT JOIN component_installations AS current ON current.component_id = components.id AND current.removed_at IS NULL WHERE current.bike_id = ? ORDER BY components.id"#,
+            bike_id
+        )
+        .fetch_all(&self.0)
+        .await?
+        .into_models()?;
+
+        Ok(models)
+    }
+
+    pub async fn get_one(&self, component_id: i64) -> AppResult<ComponentModel> {
+        let mut conn = self.0.acquire().await?;
+        Self::get_one_in(&mut conn, component_id).await
+    }
+
+    async fn get_one_in(
+        conn: &mut SqliteConnection,
+        component_id: i64,
+    ) -> AppResult<ComponentModel> {
+        let model = sqlx::query_as!(
+            ComponentRaw,
+            r#"SELECT components.id AS "id!", components.owner_id AS "owner_id!", components.kind AS "kind!", components.brand, components.model, components.initial_distance AS "initial_distance!", components.created_at AS "created_at!", current.bike_id AS "bike_id?", current.installed_at AS "installed_at?", components.initial_distance + COALESCE((SELECT SUM(rides.distance) FROM component_installations AS past JOIN rides ON rides.bike_id = past.bike_id AND rides.date >= past.installed_at AND (past.removed_at IS NULL OR rides.date < past.removed_at) WHERE past.component_id = components.id AND rides.deleted_at IS NULL), 0.0) AS "distance!: f64" FROM components LEFT JOIN component_installations AS current ON current.component_id = components.id AND current.removed_at IS NULL WHERE components.id = ?"#,
+            component_id
+        )
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Component {component_id} not found")))?
+        .try_into()?;
+
+        Ok(model)
+    }
+
+    pub async fn get_installations(&self, component_id: i64) -> AppResult<Vec<InstallationModel>> {
+        let models = sqlx::query_as!(
+            InstallationRaw,
+            r#"SELECT id AS "id!", component_id AS "component_id!", bike_id AS "bike_id!", installed_at AS "installed_at!", removed_at, COALESCE((SELECT SUM(rides.distance) FROM rides WHERE rides.bike_id = component_installations.bike_id AND rides.date >= component_installations.installed_at AND (component_installations.removed_at IS NULL OR rides.date < component_installations.removed_at) AND rides.deleted_at IS NULL), 0.0) AS "distance!: f64" FROM component_installations WHERE component_id = ? ORDER BY installed_at DESC, id DESC"#,
+            component_id
+        )
+        .fetch_all(&self.0)
+        .await?
+        .into_models()?;
+
+        Ok(models)
+    }
+
+    pub async fn create(
+        &self,
+        owner_id: i64,
+        component: &ComponentPartial,
+        install: Option<&ComponentInstall>,
+    ) -> AppResult<ComponentModel> {
+        let mut tx = self.0.begin().await?;
+
+        let kind = component.kind.as_str();
+        let created_at = format_date_time(&Utc::now().naive_utc());
+        let id = sqlx::query!(
+            "INSERT INTO components (owner_id, kind, brand, model, initial_distance, created_at) VALUES (?, ?, ?, ?, ?, ?)",
+            owner_id,
+            kind,
+            component.brand,
+            component.model,
+            component.initial_distance,
+            created_at
+        )
+        .execute(&mut *tx)
+        .await?
+        .last_insert_rowid();
+
+        if let Some(install) = install {
+            Self::install_in(&mut tx, id, install).await?;
+        }
+
+        let model = Self::get_one_in(&mut tx, id).await?;
+        tx.commit().await?;
+        Ok(model)
+    }
+
+    pub async fn update(
+        &self,
+        component_id: i64,
+        component: &ComponentPartial,
+    ) -> AppResult<ComponentModel> {
+        let kind = component.kind.as_str();
+        sqlx::query!(
+            "UPDATE components SET kind = ?, brand = ?, model = ?, initial_distance = ? WHERE id = ?",
+            kind,
+            component.brand,
+            component.model,
+            component.initial_distance,
+            component_id
+        )
+        .execute(&self.0)
+        .await?;
+
+        self.get_one(component_id).await
+    }
+
+    pub async fn delete(&self, component_id: i64) -> AppResult<()> {
+        sqlx::query!("DELETE FROM components WHERE id = ?", component_id)
+            .execute(&self.0)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn install(
+        &self,
+        component_id: i64,
+        install: &ComponentInstall,
+    ) -> AppResult<ComponentModel> {
+        let mut tx = self.0.begin().await?;
+        Self::install_in(&mut tx, component_id, install).await?;
+        let model = Self::get_one_in(&mut tx, component_id).await?;
+        tx.commit().await?;
+        Ok(model)
+    }
+
+    pub async fn remove(
+        &self,
+        component_id: i64,
+        remove: &ComponentRemove,
+    ) -> AppResult<ComponentModel> {
+        let mut tx = self.0.begin().await?;
+        let current = Self::get_latest_installation(&mut tx, component_id)
+            .await?
+            .filter(|latest| latest.removed_at.is_none())
+            .ok_or_else(|| {
+                AppError::BadRequest(format!("Component {component_id} is not installed"))
+            })?;
+
+        Self::close_installation(&mut tx, &current, &remove.date).await?;
+        let model = Self::get_one_in(&mut tx, component_id).await?;
+        tx.commit().await?;
+        Ok(model)
+    }
+
+    /// Closes the current installation on the install date, which swaps
+    /// the component over when it is installed on another bike.
+    async fn install_in(
+        conn: &mut SqliteConnection,
+        component_id: i64,
+        install: &ComponentInstall,
+    ) -> AppResult<()> {
+        match Self::get_latest_installation(conn, component_id).await? {
+            Some(current) if current.removed_at.is_none() => {
+                if current.bike_id == install.bike_id {
+                    return Err(AppError::BadRequest(format!(
+                        "Component {component_id} is already installed on bike {}",
+                        current.bike_id
+                    )));
+                }
+                Self::close_installation(conn, &current, &install.date).await?;
+            }
+            Some(LatestInstallation {
+                removed_at: Some(removed_at),
+                ..
+            }) if install.date < removed_at => {
+                return Err(AppError::BadRequest(format!(
+                    "Component {component_id} can't be installed before {removed_at}"
+                )));
+            }
+            _ => {}
+        }
+
+        let installed_at = format_date(&install.date);
+        sqlx::query!(
+            "INSERT INTO component_installations (component_id, bike_id, installed_at) VALUES (?, ?, ?)",
+            component_id,
+            install.bike_id,
+            installed_at
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn close_installation(
+        conn: &mut SqliteConnection,
+        installation: &LatestInstallation,
+        removed_at: &NaiveDate,
+    ) -> AppResult<()> {
+        if *removed_at < installation.installed_at {
+            return Err(AppError::BadRequest(format!(
+                "Component can't be removed before {}",
+                installation.installed_at
+            )));
+        }
+
+        let removed_at = format_date(removed_at);
+        sqlx::query!(
+            "UPDATE component_installations SET removed_at = ? WHERE id = ?",
+            removed_at,
+            installation.id
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_latest_installation(
+        conn: &mut SqliteConnection,
+        component_id: i64,
+    ) -> AppResult<Option<LatestInstallation>> {
+        let latest = sqlx::query!(
+            r#"SELECT id AS "id!", bike_id, installed_at, removed_at FROM component_installations WHERE component_id = ? ORDER BY installed_at DESC, id DESC LIMIT 1"#,
+            component_id
+        )
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        let Some(latest) = latest else {
+            return Ok(None);
+        };
+
+        Ok(Some(LatestInstallation {
+            id: latest.id,
+            bike_id: latest.bike_id,
+            installed_at: parse_date(&latest.installed_at).map_err(anyhow::Error::from)?,
+            removed_at: latest
+                .removed_at
+                .map(|s| parse_date(&s))
+                .transpose()
+                .map_err(anyhow::Error::from)?,
+        }))
+    }
+}
+
+struct LatestInstallation {
+    id: i64,
+    bike_id: i64,
+    installed_at: NaiveDate,
+    removed_at: Option<NaiveDate>,
+}
@@ -0,0 +1,131 @@
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::routing::{delete, get, post, put};
+use axum::{Extension, Json, Router};
+
+use crate::services::auth::models::SessionModel;
+use crate::services::bikes::members::models::BikeRole;
+use crate::services::bikes::repository::BikeRepository;
+use crate::utility::error::AppResult;
+use crate::utility::state::AppState;
+
+use super::models::{
+    ComponentCreate, ComponentInstall, ComponentModel, ComponentPartial, ComponentRemove,
+    InstallationModel,
+};
+use super::repository::ComponentRepository;
+
+pub fn router_with_auth() -> Router<AppState> {
+    Router::new()
+        .route("/", get(get_all_components))
+        .route("/", post(create_component))
+        .route("/{id}", get(get_component))
+        .route("/{id}", put(update_component))
+        .route("/{id}", delete(delete_component))
+        .route("/{id}/installations", get(get_installations))
+        .route("/{id}/install", post(install_component))
+        .route("/{id}/remove", post(remove_component))
+}
+
+async fn get_all_components(
+    State(component_repo): State<ComponentRepository>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<Json<Vec<ComponentModel>>> {
+    let models = component_repo.get_all(session.user_id).await?;
+    Ok(Json(models))
+}
+
+async fn create_component(
+    State(bike_repo): State<BikeRepository>,
+    State(component_repo): State<ComponentRepository>,
+    Extension(session): Extension<SessionModel>,
+    Json(payload): Json<ComponentCreate>,
+) -> AppResult<(StatusCode, Json<ComponentModel>)> {
+    if let Some(install) = &payload.install {
+        bike_repo
+            .assert_role(install.bike_id, session.user_id, BikeRole::Owner)
+            .await?;
+    }
+
+    let model = component_repo
+        .create(
+            session.user_id,
+            &payload.component,
+            payload.install.as_ref(),
+        )
+        .await?;
+    Ok((StatusCode::CREATED, Json(model)))
+}
+
+async fn get_component(
+    State(component_repo): State<ComponentRepository>,
+    Path(id): Path<i64>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<Json<ComponentModel>> {
+    component_repo.assert_owner(id, session.user_id).await?;
+
+    let model = component_repo.get_one(id).await?;
+    Ok(Json(model))
+}
+
+async fn update_component(
+    State(component_repo): State<ComponentRepository>,
+    Path(id): Path<i64>,
+    Extension(session): Extension<SessionModel>,
+    Json(payload): Json<ComponentPartial>,
+) -> AppResult<Json<ComponentModel>> {
+    component_repo.assert_owner(id, session.user_id).await?;
+
+    let model = component_repo.update(id, &payload).await?;
+    Ok(Json(model))
+}
+
+async fn delete_component(
+    State(component_repo): State<ComponentRepository>,
+    Path(id): Path<i64>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<StatusCode> {
+    component_repo.assert_owner(id, session.user_id).await?;
+
+    component_repo.delete(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_installations(
+    State(component_repo): State<ComponentRepository>,
+    Path(id): Path<i64>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<Json<Vec<InstallationModel>>> {
+    component_repo.assert_owner(id, session.user_id).await?;
+
+    let models = component_repo.get_installations(id).await?;
+    Ok(Json(models))
+}
+
+async fn install_component(
+    State(bike_repo): State<BikeRepository>,
+    State(component_repo): State<ComponentRepository>,
+    Path(id): Path<i64>,
+    Extension(session): Extension<SessionModel>,
+    Json(payload): Json<ComponentInstall>,
+) -> AppResult<Json<ComponentModel>> {
+    component_repo.assert_owner(id, session.user_id).await?;
+    bike_repo
+        .assert_role(payload.bike_id, session.user_id, BikeRole::Owner)
+        .await?;
+
+    let model = component_repo.install(id, &payload).await?;
+    Ok(Json(model))
+}
+
+async fn remove_component(
+    State(component_repo): State<ComponentRepository>,
+    Path(id): Path<i64>,
+    Extension(session): Extension<SessionModel>,
+    Json(payload): Json<ComponentRemove>,
+) -> AppResult<Json<ComponentModel>> {
+    component_repo.assert_owner(id, session.user_id).await?;
+
+    let model = component_repo.remove(id, &payload).await?;
+    Ok(Json(model))
+}
@@ -5,11 +5,18 @@ use axum::{
     Router,
 };
 use csv::Writer;
+use sqlx::SqlitePool;
 
 use crate::{
-    services::bikes::rides::{
-        models::{RideModel, RidePartial},
-        repository::RideRepository,
+    services::{
+        bikes::rides::{
+            models::{RideModel, RidePartial},
+            repository::RideRepository,
+        },
+        history::{
+            models::{Actor, HistoryAction, HistoryEntity},
+            repository::HistoryRepository,
+        },
     },
     utility::{
         error::{AppError, AppResult},
@@ -41,7 +48,7 @@ async fn export_data(State(ride_repo): State<RideRepository>) -> AppResult<(Head
 }
 
 async fn import_data(
-    State(ride_repo): State<RideRepository>,
+    State(pool): State<SqlitePool>,
     mut multipart: Multipart,
 ) -> AppResult<String> {
     let mut file_content = None;
@@ -56,17 +63,27 @@ async fn import_data(
         let mut reader = csv::Reader::from_reader(content.as_slice());
         for result in reader.deserialize() {
             let ride: RideModel = result?;
-            ride_repo
-                .create(
-                    ride.bike_id,
-                    &RidePartial {
-                        date: ride.date,
-                        distance: ride.distance,
-                        description: ride.description,
-                        strava_ride: ride.strava_ride,
-                    },
-                )
-                .await?;
+            let new = RidePartial {
+                date: ride.date,
+                distance: ride.distance,
+                description: ride.description,
+                strava_ride: ride.strava_ride,
+                moving_time: ride.moving_time,
+            };
+            let mut tx = pool.begin().await?;
+            let created =
+                RideRepository::create_in(&mut tx, ride.bike_id, ride.rider_id, &new).await?;
+            HistoryRepository::record_in(
+                &mut tx,
+                HistoryEntity::Ride,
+                created.id,
+                HistoryAction::Create,
+                Actor::Import,
+                None,
+                Some(&created),
+            )
+            .await?;
+            tx.commit().await?;
         }
 
         return Ok(String::from("Data imported"));
@@ -0,0 +1,63 @@
+use std::f64::consts::PI;
+
+use serde::Deserialize;
+
+use crate::utility::geometry::{BoundingBox, Point};
+
+pub const TILE_SIZE: u32 = 256;
+pub const MAX_ZOOM: u32 = 20;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeatmapFilter {
+    pub bike_id: Option<i64>,
+    pub year: Option<i32>,
+    pub tag: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Tile {
+    pub z: u32,
+    pub x: u32,
+    pub y: u32,
+}
+
+impl Tile {
+    pub fn new(z: u32, x: u32, y: u32) -> Option<Self> {
+        if z > MAX_ZOOM || x >= 1 << z || y >= 1 << z {
+            return None;
+        }
+
+        Some(Tile { z, x, y })
+    }
+
+    /// Geographic bounds of the tile, extended by `margin` pixels on each side.
+    pub fn bounds(&self, margin: f64) -> BoundingBox {
+        let scale = f64::from(1u32 << self.z) * f64::from(TILE_SIZE);
+        let left = f64::from(self.x * TILE_SIZE) - margin;
+        let top = f64::from(self.y * TILE_SIZE) - margin;
+        let right = f64::from((self.x + 1) * TILE_SIZE) + margin;
+        let bottom = f64::from((self.y + 1) * TILE_SIZE) + margin;
+
+        let lon = |px: f64| px / scale * 360.0 - 180.0;
+        let lat = |py: f64| (PI * (1.0 - 2.0 * py / scale)).sinh().atan().to_degrees();
+        BoundingBox {
+            min_lat: lat(bottom),
+            min_lon: lon(left),
+            max_lat: lat(top),
+            max_lon: lon(right),
+        }
+    }
+
+    /// Projects the point to web mercator pixel coordinates relative to the tile.
+    pub fn project(&self, point: &Point) -> (f64, f64) {
+        let scale = f64::from(1u32 << self.z) * f64::from(TILE_SIZE);
+        let lat = point.lat.clamp(-85.0511, 85.0511).to_radians();
+        let x = (point.lon + 180.0) / 360.0 * scale;
+        let y = (1.0 - (lat.tan() + 1.0 / lat.cos()).ln() / PI) / 2.0 * scale;
+        (
+            x - f64::from(self.x * TILE_SIZE),
+            y - f64::from(self.y * TILE_SIZE),
+        )
+    }
+}
@@ -0,0 +1,124 @@
+use crate::services::bikes::rides::tracks::models::TrackModel;
+
+use super::models::{Tile, TILE_SIZE};
+
+/// Number of rides over a pixel at which the heat is fully saturated.
+const SATURATION: f64 = 25.0;
+const SIZE: usize = TILE_SIZE as usize;
+
+/// Renders the tracks into a transparent PNG tile where each pixel
+/// is colored by the number of distinct tracks passing through it.
+pub fn render_tile(tile: &Tile, tracks: &[TrackModel]) -> anyhow::Result<Vec<u8>> {
+    let mut counts = vec![0u32; SIZE * SIZE];
+    let mut stamps = vec![usize::MAX; SIZE * SIZE];
+
+    for (index, track) in tracks.iter().enumerate() {
+        let mut mark = |x: i64, y: i64| {
+            for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                let (px, py) = (x + dx, y + dy);
+                if px < 0 || py < 0 || px >= SIZE as i64 || py >= SIZE as i64 {
+                    continue;
+                }
+
+                let offset = py as usize * SIZE + px as usize;
+                if stamps[offset] != index {
+                    stamps[offset] = index;
+                    counts[offset] += 1;
+                }
+            }
+        };
+
+        let projected = track
+            .points
+            .iter()
+            .map(|p| tile.project(p))
+            .collect::<Vec<_>>();
+        for segment in projected.windows(2) {
+            if let Some((start, end)) = clip(segment[0], segment[1]) {
+                draw_line(start, end, &mut mark);
+            }
+        }
+    }
+
+    let mut pixels = Vec::with_capacity(SIZE * SIZE * 4);
+    for count in counts {
+        pixels.extend_from_slice(&heat_color(count));
+    }
+
+    let mut data = Vec::new();
+    let mut encoder = png::Encoder::new(&mut data, TILE_SIZE, TILE_SIZE);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&pixels)?;
+    writer.finish()?;
+
+    Ok(data)
+}
+
+fn heat_color(count: u32) -> [u8; 4] {
+    if count == 0 {
+        return [0, 0, 0, 0];
+    }
+
+    let heat = ((1.0 + f64::from(count)).ln() / (1.0 + SATURATION).ln()).min(1.0);
+    let channel = |value: f64| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    [
+        channel(0.6 + heat * 1.2),
+        channel(heat * 2.0 - 0.6),
+        channel(heat * 3.0 - 2.0),
+        channel(0.55 + heat * 0.45),
+    ]
+}
+
+/// Clips the segment to the tile area using the Liang–Barsky algorithm.
+fn clip(start: (f64, f64), end: (f64, f64)) -> Option<((f64, f64), (f64, f64))> {
+    let (min, max) = (-2.0, SIZE as f64 + 2.0);
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let mut t0: f64 = 0.0;
+    let mut t1: f64 = 1.0;
+
+    for (p, q) in [
+        (-dx, start.0 - min),
+        (dx, max - start.0),
+        (-dy, start.1 - min),
+        (dy, max - start.1),
+    ] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+            continue;
+        }
+
+        let t = q / p;
+        if p < 0.0 {
+            t0 = t0.max(t);
+        } else {
+            t1 = t1.min(t);
+        }
+        if t0 > t1 {
+            return None;
+        }
+    }
+
+    Some((
+        (start.0 + t0 * dx, start.1 + t0 * dy),
+        (start.0 + t1 * dx, start.1 + t1 * dy),
+    ))
+}
+
+fn draw_line(start: (f64, f64), end: (f64, f64), mark: &mut impl FnMut(i64, i64)) {
+    let steps = (end.0 - start.0).abs().max((end.1 - start.1).abs()).ceil() as i64;
+    if steps == 0 {
+        mark(start.0.floor() as i64, start.1.floor() as i64);
+        return;
+    }
+
+    for step in 0..=steps {
+        let t = step as f64 / steps as f64;
+        let x = start.0 + (end.0 - start.0) * t;
+        let y = start.1 + (end.1 - start.1) * t;
+        mark(x.floor() as i64, y.floor() as i64);
+    }
+}
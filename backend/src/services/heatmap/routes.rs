@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use axum::extract::{Path, Query, State};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Extension, Router};
+use chrono::{NaiveDateTime, Utc};
+use tokio::sync::Mutex;
+
+use crate::services::auth::models::SessionModel;
+use crate::services::bikes::members::models::BikeRole;
+use crate::services::bikes::repository::BikeRepository;
+use crate::services::bikes::rides::models::normalize_tag;
+use crate::services::bikes::rides::routes::year_range;
+use crate::services::bikes::rides::tracks::models::TrackVersion;
+use crate::services::bikes::rides::tracks::repository::TrackRepository;
+use crate::utility::error::{AppError, AppResult};
+use crate::utility::state::AppState;
+use crate::utility::versioning::if_none_match;
+
+use super::models::{HeatmapFilter, Tile};
+use super::render::render_tile;
+
+const MAX_CACHED_TILES: usize = 2048;
+/// Tiles are reused for a minute before the client revalidates them with the entity tag.
+const TILE_CACHE_CONTROL: &str = "private, max-age=60";
+/// Margin in pixels so that lines close to the tile edge are not cut off.
+const TILE_MARGIN: f64 = 4.0;
+
+type TileKey = (i64, Tile, HeatmapFilter);
+
+struct CachedTile {
+    version: TrackVersion,
+    data: Vec<u8>,
+    used_at: NaiveDateTime,
+}
+
+static TILES: LazyLock<Mutex<HashMap<TileKey, CachedTile>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+pub fn router_with_auth() -> Router<AppState> {
+    Router::new().route("/{z}/{x}/{tile}", get(get_tile))
+}
+
+async fn get_tile(
+    State(bike_repo): State<BikeRepository>,
+    State(track_repo): State<TrackRepository>,
+    Path((z, x, y)): Path<(u32, u32, String)>,
+    Query(filter): Query<HeatmapFilter>,
+    Extension(session): Extension<SessionModel>,
+    headers: HeaderMap,
+) -> AppResult<Response> {
+    let tile = y
+        .strip_suffix(".png")
+        .and_then(|y| y.parse::<u32>().ok())
+        .and_then(|y| Tile::new(z, x, y))
+        .ok_or_else(|| AppError::NotFound(format!("No tile {z}/{x}/{y}")))?;
+    let range = filter.year.map(year_range).transpose()?;
+
+    if let Some(bike_id) = filter.bike_id {
+        bike_repo
+            .assert_role(bike_id, session.user_id, BikeRole::Viewer)
+            .await?;
+    }
+
+    let version = track_repo.get_version(session.user_id).await?;
+    let etag = etag(&version);
+
+    let mut response_headers = HeaderMap::new();
+    response_headers.insert(
+        header::ETAG,
+        HeaderValue::from_str(&etag).map_err(anyhow::Error::from)?,
+    );
+    response_headers.insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static(TILE_CACHE_CONTROL),
+    );
+
+    if if_none_match(&headers, &etag) {
+        return Ok((StatusCode::NOT_MODIFIED, response_headers).into_response());
+    }
+
+    let key = (session.user_id, tile, filter.clone());
+    let data = match cached_tile(&key, &version).await {
+        Some(data) => data,
+        None => {
+            let tracks = track_repo
+                .get_all_in_bounds(
+                    session.user_id,
+                    &tile.bounds(TILE_MARGIN),
+                    filter.bike_id,
+                    range,
+                    filter.tag.as_deref().and_then(normalize_tag),
+                )
+                .await?;
+            let data = tokio::task::spawn_blocking(move || render_tile(&tile, &tracks))
+                .await
+                .map_err(anyhow::Error::from)??;
+            cache_tile(key, version, data.clone()).await;
+            data
+        }
+    };
+
+    response_headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("image/png"));
+    Ok((response_headers, data).into_response())
+}
+
+/// Same for all tiles of the user, made of the track count and the time of the last change.
+fn etag(version: &TrackVersion) -> String {
+    let updated_at = version
+        .updated_at
+        .as_deref()
+        .unwrap_or_default()
+        .chars()
+        .filter(char::is_ascii_digit)
+        .collect::<String>();
+    format!("\"{}-{updated_at}\"", version.tracks)
+}
+
+async fn cached_tile(key: &TileKey, version: &TrackVersion) -> Option<Vec<u8>> {
+    let mut tiles = TILES.lock().await;
+    let cached = tiles.get_mut(key)?;
+    if cached.version != *version {
+        return None;
+    }
+
+    cached.used_at = Utc::now().naive_utc();
+    Some(cached.data.clone())
+}
+
+async fn cache_tile(key: TileKey, version: TrackVersion, data: Vec<u8>) {
+    let mut tiles = TILES.lock().await;
+    if tiles.len() >= MAX_CACHED_TILES && !tiles.contains_key(&key) {
+        let oldest = tiles
+            .iter()
+            .min_by_key(|(_, cached)| cached.used_at)
+            .map(|(key, _)| key.clone());
+        if let Some(oldest) = oldest {
+            tiles.remove(&oldest);
+        }
+    }
+
+    let cached = CachedTile {
+        version,
+        data,
+        used_at: Utc::now().naive_utc(),
+    };
+    tiles.insert(key, cached);
+}
@@ -0,0 +1,151 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::utility::{db_extensions::Model, db_format::parse_date_time};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HistoryEntity {
+    Bike,
+    Ride,
+}
+
+impl HistoryEntity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HistoryEntity::Bike => "bike",
+            HistoryEntity::Ride => "ride",
+        }
+    }
+}
+
+impl FromStr for HistoryEntity {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bike" => Ok(HistoryEntity::Bike),
+            "ride" => Ok(HistoryEntity::Ride),
+            _ => Err(anyhow!("Unknown history entity {s}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HistoryAction {
+    Create,
+    Update,
+    Delete,
+    Restore,
+}
+
+impl HistoryAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HistoryAction::Create => "create",
+            HistoryAction::Update => "update",
+            HistoryAction::Delete => "delete",
+            HistoryAction::Restore => "restore",
+        }
+    }
+}
+
+impl FromStr for HistoryAction {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "create" => Ok(HistoryAction::Create),
+            "update" => Ok(HistoryAction::Update),
+            "delete" => Ok(HistoryAction::Delete),
+            "restore" => Ok(HistoryAction::Restore),
+            _ => Err(anyhow!("Unknown history action {s}")),
+        }
+    }
+}
+
+/// Who made the change, the user id is known for changes made through a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase", tag = "type", content = "userId")]
+pub enum Actor {
+    User(i64),
+    #[serde(rename = "strava-sync")]
+    StravaSync(i64),
+    Import,
+}
+
+impl Actor {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Actor::User(_) => "user",
+            Actor::StravaSync(_) => "strava-sync",
+            Actor::Import => "import",
+        }
+    }
+
+    pub fn user_id(&self) -> Option<i64> {
+        match self {
+            Actor::User(id) | Actor::StravaSync(id) => Some(*id),
+            Actor::Import => None,
+        }
+    }
+
+    fn parse(actor: &str, user_id: Option<i64>) -> anyhow::Result<Self> {
+        let user_id = || user_id.ok_or_else(|| anyhow!("Missing user of actor {actor}"));
+        match actor {
+            "user" => Ok(Actor::User(user_id()?)),
+            "strava-sync" => Ok(Actor::StravaSync(user_id()?)),
+            "import" => Ok(Actor::Import),
+            _ => Err(anyhow!("Unknown actor {actor}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HistoryRaw {
+    pub id: i64,
+    pub entity: String,
+    pub entity_id: i64,
+    pub action: String,
+    pub actor: String,
+    pub actor_id: Option<i64>,
+    pub before: Option<String>,
+    pub after: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryModel {
+    pub id: i64,
+    pub entity: HistoryEntity,
+    pub entity_id: i64,
+    pub action: HistoryAction,
+    pub actor: Actor,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub created_at: NaiveDateTime,
+}
+
+impl Model<HistoryRaw> for HistoryModel {}
+
+impl TryFrom<HistoryRaw> for HistoryModel {
+    type Error = anyhow::Error;
+    fn try_from(raw: HistoryRaw) -> Result<Self, Self::Error> {
+        let before = raw.before.map(|s| serde_json::from_str(&s)).transpose()?;
+        let after = raw.after.map(|s| serde_json::from_str(&s)).transpose()?;
+        Ok(HistoryModel {
+            id: raw.id,
+            entity: raw.entity.parse()?,
+            entity_id: raw.entity_id,
+            action: raw.action.parse()?,
+            actor: Actor::parse(&raw.actor, raw.actor_id)?,
+            before,
+            after,
+            created_at: parse_date_time(&raw.created_at)?,
+        })
+    }
+}
@@ -0,0 +1,74 @@
+use chrono::Utc;
+use serde::Serialize;
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::utility::{db_extensions::IntoModels, db_format::format_date_time, error::AppResult};
+
+use super::models::{Actor, HistoryAction, HistoryEntity, HistoryModel, HistoryRaw};
+
+#[derive(Clone)]
+pub struct HistoryRepository(SqlitePool);
+
+impl HistoryRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self(pool)
+    }
+
+    pub async fn get_all(
+        &self,
+        entity: HistoryEntity,
+        entity_id: i64,
+    ) -> AppResult<Vec<HistoryModel>> {
+        let entity = entity.as_str();
+        let models = sqlx::query_as!(
+            HistoryRaw,
+            r#"SELECT id AS "id!", entity, entity_id, action, actor, actor_id, before, after, created_at FROM history WHERE entity = ? AND entity_id = ? ORDER BY id DESC"#,
+            entity,
+            entity_id
+        )
+        .fetch_all(&self.0)
+        .await?
+        .into_models()?;
+
+        Ok(models)
+    }
+
+    pub async fn record_in<T: Serialize>(
+        conn: &mut SqliteConnection,
+        entity: HistoryEntity,
+        entity_id: i64,
+        action: HistoryAction,
+        actor: Actor,
+        before: Option<&T>,
+        after: Option<&T>,
+    ) -> AppResult<()> {
+        let entity = entity.as_str();
+        let action = action.as_str();
+        let actor_name = actor.as_str();
+        let actor_id = actor.user_id();
+        let before = before
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(anyhow::Error::from)?;
+        let after = after
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(anyhow::Error::from)?;
+        let now = format_date_time(&Utc::now().naive_utc());
+        sqlx::query!(
+            "INSERT INTO history (entity, entity_id, action, actor, actor_id, before, after, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            entity,
+            entity_id,
+            action,
+            actor_name,
+            actor_id,
+            before,
+            after,
+            now
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,4 @@
+pub mod middleware;
+pub mod models;
+pub mod repository;
+pub mod routes;
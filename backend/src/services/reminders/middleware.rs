@@ -0,0 +1,34 @@
+use axum::{
+    extract::{Request, State},
+    http::Method,
+    middleware::Next,
+    response::Response,
+};
+
+use crate::services::auth::models::SessionModel;
+
+use super::repository::ReminderRepository;
+
+/// Re-evaluates the reminders of the user and their fellow bike members after a successful
+/// change, so that changes to rides from any route keep the reminders up to date.
+pub async fn evaluation_layer(
+    State(reminder_repo): State<ReminderRepository>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let changes = req.method() != Method::GET && req.method() != Method::HEAD;
+    let user_id = req
+        .extensions()
+        .get::<SessionModel>()
+        .map(|session| session.user_id);
+
+    let response = next.run(req).await;
+    if let Some(user_id) = user_id.filter(|_| changes && response.status().is_success()) {
+        // The change is committed already, reminders catch up on the next change
+        if let Err(e) = reminder_repo.evaluate_members(user_id).await {
+            println!("Failed to evaluate reminders: {}", e.detailed());
+        }
+    }
+
+    response
+}
@@ -0,0 +1,142 @@
+use chrono::{Months, NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::utility::{
+    db_extensions::Model,
+    db_format::{parse_date, parse_date_time},
+};
+
+/// Share of the interval left at which a reminder is listed as upcoming.
+const UPCOMING_SHARE: f64 = 0.1;
+
+/// Reminder rule with the usage of its bike or component since it was last done.
+#[derive(Debug, Clone)]
+pub struct ReminderRaw {
+    pub id: i64,
+    pub owner_id: i64,
+    pub bike_id: Option<i64>,
+    pub component_id: Option<i64>,
+    pub name: String,
+    pub every_distance: Option<f64>,
+    pub every_hours: Option<f64>,
+    pub every_months: Option<i64>,
+    pub last_done_at: String,
+    pub due_since: Option<String>,
+    pub created_at: String,
+    pub distance_since: f64,
+    pub moving_time_since: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReminderStatus {
+    Due,
+    Upcoming,
+    Ok,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReminderModel {
+    pub id: i64,
+    pub owner_id: i64,
+    pub bike_id: Option<i64>,
+    pub component_id: Option<i64>,
+    pub name: String,
+    pub every_distance: Option<f64>,
+    pub every_hours: Option<f64>,
+    pub every_months: Option<i64>,
+    pub last_done_at: NaiveDate,
+    pub due_since: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub status: ReminderStatus,
+    pub remaining_distance: Option<f64>,
+    pub remaining_hours: Option<f64>,
+    pub due_date: Option<NaiveDate>,
+    pub remaining_days: Option<i64>,
+}
+
+impl Model<ReminderRaw> for ReminderModel {}
+
+impl TryFrom<ReminderRaw> for ReminderModel {
+    type Error = anyhow::Error;
+    fn try_from(raw: ReminderRaw) -> Result<Self, Self::Error> {
+        let today = Utc::now().date_naive();
+        let last_done_at = parse_date(&raw.last_done_at)?;
+
+        let remaining_distance = raw.every_distance.map(|every| every - raw.distance_since);
+        let remaining_hours = raw
+            .every_hours
+            .map(|every| every - raw.moving_time_since as f64 / 3600.0);
+        let due_date = raw
+            .every_months
+            .and_then(|every| last_done_at.checked_add_months(Months::new(every as u32)));
+        let remaining_days = due_date.map(|due_date| (due_date - today).num_days());
+
+        let interval_days = due_date.map(|due_date| (due_date - last_done_at).num_days() as f64);
+        let shares = [
+            raw.every_distance.zip(remaining_distance),
+            raw.every_hours.zip(remaining_hours),
+            interval_days.zip(remaining_days.map(|days| days as f64)),
+        ];
+        let status = shares
+            .into_iter()
+            .flatten()
+            .map(|(every, remaining)| {
+                if remaining <= 0.0 {
+                    ReminderStatus::Due
+                } else if remaining <= every * UPCOMING_SHARE {
+                    ReminderStatus::Upcoming
+                } else {
+                    ReminderStatus::Ok
+                }
+            })
+            .min()
+            .unwrap_or(ReminderStatus::Ok);
+
+        Ok(ReminderModel {
+            id: raw.id,
+            owner_id: raw.owner_id,
+            bike_id: raw.bike_id,
+            component_id: raw.component_id,
+            name: raw.name,
+            every_distance: raw.every_distance,
+            every_hours: raw.every_hours,
+            every_months: raw.every_months,
+            last_done_at,
+            due_since: raw.due_since.map(|s| parse_date_time(&s)).transpose()?,
+            created_at: parse_date_time(&raw.created_at)?,
+            status,
+            remaining_distance,
+            remaining_hours,
+            due_date,
+            remaining_days,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReminderPartial {
+    pub bike_id: Option<i64>,
+    pub component_id: Option<i64>,
+    pub name: String,
+    pub every_distance: Option<f64>,
+    pub every_hours: Option<f64>,
+    pub every_months: Option<u32>,
+    /// Defaults to today
+    pub last_done_at: Option<NaiveDate>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReminderDone {
+    /// Defaults to today
+    pub date: Option<NaiveDate>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReminderQuery {
+    /// Include reminders that are neither due nor upcoming
+    #[serde(default)]
+    pub all: bool,
+}
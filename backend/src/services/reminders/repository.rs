@@ -0,0 +1,174 @@
+use chrono::{NaiveDate, Utc};
+use sqlx::SqlitePool;
+
+use crate::utility::{
+    db_extensions::IntoModels,
+    db_format::{format_date, format_date_time},
+    error::{AppError, AppResult},
+};
+
+use super::models::{ReminderModel, ReminderPartial, ReminderRaw, ReminderStatus};
+
+#[derive(Clone)]
+pub struct ReminderRepository(SqlitePool);
+
+impl ReminderRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self(pool)
+    }
+
+    pub async fn assert_owner(&self, reminder_id: i64, user_id: i64) -> AppResult<()> {
+        let something = sqlx::query!(
+            "SELECT id FROM reminders WHERE id = ? AND owner_id = ?",
+            reminder_id,
+            user_id
+        )
+        .fetch_optional(&self.0)
+        .await?;
+
+        match something {
+            Some(_) => Ok(()),
+            None => Err(AppError::Forbidden),
+        }
+    }
+
+    pub async fn get_all(&self, owner_id: i64) -> AppResult<Vec<ReminderModel>> {
+        // Distance and moving time of the rides since the reminder was last done, made on
+        // its bike or on the bikes its component was installed on at the time
+        let models = sqlx::query_as!(
+            ReminderRaw,
+            r#"SELECT id AS "id!", owner_id AS "owner_id!", bike_id, component_id, name AS "name!", every_distance, every_hours, every_months, last_done_at AS "last_done_at!", due_since, created_at AS "created_at!", COALESCE((SELECT SUM(rides.distance) FROM rides WHERE rides.deleted_at IS NULL AND rides.date >= reminders.last_done_at AND (rides.bike_id = reminders.bike_id OR EXISTS (SELECT 1 FROM component_installations AS installations WHERE installations.component_id = reminders.component_id AND installations.bike_id = rides.bike_id AND rides.date >= installations.installed_at AND (installations.removed_at IS NULL OR rides.date < installations.removed_at)))), 0.0) AS "distance_since!: f64", COALESCE((SELECT SUM(rides.moving_time) FROM rides WHERE rides.deleted_at IS NULL AND rides.date >= reminders.last_done_at AND (rides.bike_id = reminders.bike_id OR EXISTS (SELECT 1 FROM component_installations AS installations WHERE installations.component_id = reminders.component_id AND installations.bike_id = rides.bike_id AND rides.date >= installations.installed_at AND (installations.removed_at IS NULL OR rides.date < installations.removed_at)))), 0) AS "moving_time_since!: i64" FROM reminders WHERE owner_id = ? ORDER BY id"#,
+            owner_id
+        )
+        .fetch_all(&self.0)
+        .await?
+        .into_models()?;
+
+        Ok(models)
+    }
+
+    pub async fn get_one(&self, reminder_id: i64) -> AppResult<ReminderModel> {
+        let model = sqlx::query_as!(
+            ReminderRaw,
+            r#"SELECT id AS "id!", owner_id AS "owner_id!", bike_id, component_id, name AS "name!", every_distance, every_hours, every_months, last_done_at AS "last_done_at!", due_since, created_at AS "created_at!", COALESCE((SELECT SUM(rides.distance) FROM rides WHERE rides.deleted_at IS NULL AND rides.date >= reminders.last_done_at AND (rides.bike_id = reminders.bike_id OR EXISTS (SELECT 1 FROM component_installations AS installations WHERE installations.component_id = reminders.component_id AND installations.bike_id = rides.bike_id AND rides.date >= installations.installed_at AND (installations.removed_at IS NULL OR rides.date < installations.removed_at)))), 0.0) AS "distance_since!: f64", COALESCE((SELECT SUM(rides.moving_time) FROM rides WHERE rides.deleted_at IS NULL AND rides.date >= reminders.last_done_at AND (rides.bike_id = reminders.bike_id OR EXISTS (SELECT 1 FROM component_installations AS installations WHERE installations.component_id = reminders.component_id AND installations.bike_id = rides.bike_id AND rides.date >= installations.installed_at AND (installations.removed_at IS NULL OR rides.date < installations.removed_at)))), 0) AS "moving_time_since!: i64" FROM reminders WHERE id = ?"#,
+            reminder_id
+        )
+        .fetch_optional(&self.0)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Reminder {reminder_id} not found")))?
+        .try_into()?;
+
+        Ok(model)
+    }
+
+    pub async fn create(&self, owner_id: i64, new: &ReminderPartial) -> AppResult<ReminderModel> {
+        let now = Utc::now().naive_utc();
+        let last_done_at = format_date(&new.last_done_at.unwrap_or(now.date()));
+        let created_at = format_date_time(&now);
+        let id = sqlx::query!(
+            "INSERT INTO reminders (owner_id, bike_id, component_id, name, every_distance, every_hours, every_months, last_done_at, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            owner_id,
+            new.bike_id,
+            new.component_id,
+            new.name,
+            new.every_distance,
+            new.every_hours,
+            new.every_months,
+            last_done_at,
+            created_at
+        )
+        .execute(&self.0)
+        .await?
+        .last_insert_rowid();
+
+        self.get_one(id).await
+    }
+
+    pub async fn update(
+        &self,
+        reminder_id: i64,
+        update: &ReminderPartial,
+    ) -> AppResult<ReminderModel> {
+        let last_done_at = format_date(
+            &update
+                .last_done_at
+                .unwrap_or_else(|| Utc::now().date_naive()),
+        );
+        sqlx::query!(
+            "UPDATE reminders SET bike_id = ?, component_id = ?, name = ?, every_distance = ?, every_hours = ?, every_months = ?, last_done_at = ? WHERE id = ?",
+            update.bike_id,
+            update.component_id,
+            update.name,
+            update.every_distance,
+            update.every_hours,
+            update.every_months,
+            last_done_at,
+            reminder_id
+        )
+        .execute(&self.0)
+        .await?;
+
+        self.get_one(reminder_id).await
+    }
+
+    pub async fn mark_done(&self, reminder_id: i64, date: &NaiveDate) -> AppResult<ReminderModel> {
+        let date = format_date(date);
+        sqlx::query!(
+            "UPDATE reminders SET last_done_at = ? WHERE id = ?",
+            date,
+            reminder_id
+        )
+        .execute(&self.0)
+        .await?;
+
+        self.get_one(reminder_id).await
+    }
+
+    pub async fn delete(&self, reminder_id: i64) -> AppResult<()> {
+        sqlx::query!("DELETE FROM reminders WHERE id = ?", reminder_id)
+            .execute(&self.0)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Re-evaluates the reminders of the user and everyone sharing a bike with them,
+    /// rides of shared bikes count towards reminders of all their members.
+    pub async fn evaluate_members(&self, user_id: i64) -> AppResult<()> {
+        let members = sqlx::query_scalar!(
+            r#"SELECT DISTINCT user_id AS "user_id!" FROM bike_members WHERE bike_id IN (SELECT bike_id FROM bike_members WHERE user_id = ?1) UNION SELECT ?1"#,
+            user_id
+        )
+        .fetch_all(&self.0)
+        .await?;
+
+        for member in members {
+            self.evaluate(member).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Re-evaluates the reminders of the user after their rides changed,
+    /// remembering since when each reminder is due.
+    pub async fn evaluate(&self, owner_id: i64) -> AppResult<()> {
+        let now = format_date_time(&Utc::now().naive_utc());
+        for reminder in self.get_all(owner_id).await? {
+            let is_due = reminder.status == ReminderStatus::Due;
+            if is_due == reminder.due_since.is_some() {
+                continue;
+            }
+
+            let due_since = is_due.then_some(now.as_str());
+            sqlx::query!(
+                "UPDATE reminders SET due_since = ? WHERE id = ?",
+                due_since,
+                reminder.id
+            )
+            .execute(&self.0)
+            .await?;
+        }
+
+        Ok(())
+    }
+}
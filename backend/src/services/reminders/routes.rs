@@ -0,0 +1,152 @@
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::routing::{delete, get, post, put};
+use axum::{Extension, Json, Router};
+use chrono::Utc;
+
+use crate::services::auth::models::SessionModel;
+use crate::services::bikes::members::models::BikeRole;
+use crate::services::bikes::repository::BikeRepository;
+use crate::services::components::repository::ComponentRepository;
+use crate::utility::error::{AppError, AppResult};
+use crate::utility::state::AppState;
+
+use super::models::{ReminderDone, ReminderModel, ReminderPartial, ReminderQuery, ReminderStatus};
+use super::repository::ReminderRepository;
+
+pub fn router_with_auth() -> Router<AppState> {
+    Router::new()
+        .route("/", get(get_reminders))
+        .route("/", post(create_reminder))
+        .route("/{id}", get(get_reminder))
+        .route("/{id}", put(update_reminder))
+        .route("/{id}", delete(delete_reminder))
+        .route("/{id}/done", post(mark_done))
+}
+
+/// Due and upcoming reminders first, sorted by urgency.
+async fn get_reminders(
+    State(reminder_repo): State<ReminderRepository>,
+    Query(query): Query<ReminderQuery>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<Json<Vec<ReminderModel>>> {
+    let mut models = reminder_repo
+        .get_all(session.user_id)
+        .await?
+        .into_iter()
+        .filter(|reminder| query.all || reminder.status != ReminderStatus::Ok)
+        .collect::<Vec<_>>();
+    models.sort_by_key(|reminder| (reminder.status, reminder.due_since, reminder.id));
+    Ok(Json(models))
+}
+
+async fn create_reminder(
+    State(bike_repo): State<BikeRepository>,
+    State(component_repo): State<ComponentRepository>,
+    State(reminder_repo): State<ReminderRepository>,
+    Extension(session): Extension<SessionModel>,
+    Json(payload): Json<ReminderPartial>,
+) -> AppResult<(StatusCode, Json<ReminderModel>)> {
+    validate(&bike_repo, &component_repo, &session, &payload).await?;
+
+    let model = reminder_repo.create(session.user_id, &payload).await?;
+    reminder_repo.evaluate(session.user_id).await?;
+    Ok((StatusCode::CREATED, Json(model)))
+}
+
+async fn get_reminder(
+    State(reminder_repo): State<ReminderRepository>,
+    Path(id): Path<i64>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<Json<ReminderModel>> {
+    reminder_repo.assert_owner(id, session.user_id).await?;
+
+    let model = reminder_repo.get_one(id).await?;
+    Ok(Json(model))
+}
+
+async fn update_reminder(
+    State(bike_repo): State<BikeRepository>,
+    State(component_repo): State<ComponentRepository>,
+    State(reminder_repo): State<ReminderRepository>,
+    Path(id): Path<i64>,
+    Extension(session): Extension<SessionModel>,
+    Json(payload): Json<ReminderPartial>,
+) -> AppResult<Json<ReminderModel>> {
+    reminder_repo.assert_owner(id, session.user_id).await?;
+    validate(&bike_repo, &component_repo, &session, &payload).await?;
+
+    reminder_repo.update(id, &payload).await?;
+    reminder_repo.evaluate(session.user_id).await?;
+    let model = reminder_repo.get_one(id).await?;
+    Ok(Json(model))
+}
+
+async fn delete_reminder(
+    State(reminder_repo): State<ReminderRepository>,
+    Path(id): Path<i64>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<StatusCode> {
+    reminder_repo.assert_owner(id, session.user_id).await?;
+
+    reminder_repo.delete(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn mark_done(
+    State(reminder_repo): State<ReminderRepository>,
+    Path(id): Path<i64>,
+    Extension(session): Extension<SessionModel>,
+    Json(payload): Json<ReminderDone>,
+) -> AppResult<Json<ReminderModel>> {
+    reminder_repo.assert_owner(id, session.user_id).await?;
+
+    let date = payload.date.unwrap_or_else(|| Utc::now().date_naive());
+    reminder_repo.mark_done(id, &date).await?;
+    reminder_repo.evaluate(session.user_id).await?;
+    let model = reminder_repo.get_one(id).await?;
+    Ok(Json(model))
+}
+
+async fn validate(
+    bike_repo: &BikeRepository,
+    component_repo: &ComponentRepository,
+    session: &SessionModel,
+    reminder: &ReminderPartial,
+) -> AppResult<()> {
+    match (reminder.bike_id, reminder.component_id) {
+        (Some(bike_id), None) => {
+            bike_repo
+                .assert_role(bike_id, session.user_id, BikeRole::Viewer)
+                .await?;
+        }
+        (None, Some(component_id)) => {
+            component_repo
+                .assert_owner(component_id, session.user_id)
+                .await?
+        }
+        _ => {
+            return Err(AppError::BadRequest(String::from(
+                "Reminder must be for either a bike or a component",
+            )))
+        }
+    }
+
+    let intervals = [
+        reminder.every_distance,
+        reminder.every_hours,
+        reminder.every_months.map(f64::from),
+    ];
+    if intervals.iter().all(Option::is_none) {
+        return Err(AppError::BadRequest(String::from(
+            "Reminder needs at least one interval",
+        )));
+    }
+    if intervals.iter().flatten().any(|every| *every <= 0.0) {
+        return Err(AppError::BadRequest(String::from(
+            "Reminder intervals must be positive",
+        )));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+use crate::services::bikes::rides::models::{RideModel, RidePartial};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "op")]
+pub enum BulkOperation {
+    /// Optional `idempotency_key` returns the ride created by an earlier attempt
+    #[serde(rename_all = "camelCase")]
+    Create {
+        bike_id: i64,
+        ride: RidePartial,
+        #[serde(default)]
+        idempotency_key: Option<String>,
+    },
+    /// `version` of updates, deletes and tags fails the operation when the ride was changed
+    /// meanwhile
+    #[serde(rename_all = "camelCase")]
+    Update {
+        ride_id: i64,
+        ride: RidePartial,
+        version: i64,
+    },
+    #[serde(rename_all = "camelCase")]
+    Delete { ride_id: i64, version: i64 },
+    #[serde(rename_all = "camelCase")]
+    MoveToBike { ride_id: i64, bike_id: i64 },
+    #[serde(rename_all = "camelCase")]
+    AddTag {
+        ride_id: i64,
+        tag: String,
+        version: i64,
+    },
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase", tag = "status")]
+pub enum BulkResult {
+    /// Operation succeeded, the ride is omitted for deletes
+    #[serde(rename_all = "camelCase")]
+    Ok {
+        ride: Option<RideModel>,
+        tags: Option<Vec<String>>,
+    },
+    Error {
+        /// HTTP status code the operation would fail with on its own
+        code: u16,
+        message: String,
+    },
+    /// Operation was not executed because an earlier one failed
+    Skipped,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkResponse {
+    /// Operations are applied all at once, nothing is saved when any of them fails
+    pub committed: bool,
+    pub results: Vec<BulkResult>,
+}
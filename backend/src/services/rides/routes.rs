@@ -0,0 +1,243 @@
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::{get, post};
+use axum::{Extension, Json, Router};
+use serde::Serialize;
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::services::auth::models::SessionModel;
+use crate::services::bikes::members::models::BikeRole;
+use crate::services::bikes::repository::BikeRepository;
+use crate::services::bikes::rides::models::{
+    fts_query, RideFilter, RideModel, RideMonth, RideSearchQuery, RideSearchResult, RideWithBike,
+    TaggedRide,
+};
+use crate::services::bikes::rides::repository::RideRepository;
+use crate::services::bikes::rides::routes::{cursor_headers, valid_idempotency_key, year_range};
+use crate::services::history::models::{Actor, HistoryAction, HistoryEntity};
+use crate::services::history::repository::HistoryRepository;
+use crate::utility::error::{AppError, AppResult};
+use crate::utility::state::AppState;
+use crate::utility::versioning::assert_version;
+
+use super::models::{BulkOperation, BulkResponse, BulkResult};
+
+const DEFAULT_SEARCH_LIMIT: u32 = 20;
+const MAX_BULK_OPERATIONS: usize = 1000;
+
+pub fn router_with_auth() -> Router<AppState> {
+    Router::new()
+        .route("/", get(get_all_rides))
+        .route("/search", get(search_rides))
+        .route("/bulk", post(bulk_rides))
+        .route("/monthly/{year}", get(get_monthly_rides))
+}
+
+async fn get_all_rides(
+    State(ride_repo): State<RideRepository>,
+    Query(filter): Query<RideFilter>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<(HeaderMap, Json<Vec<RideWithBike>>)> {
+    let page = ride_repo.search_for_user(session.user_id, &filter).await?;
+    let headers = cursor_headers(page.next_cursor.as_ref())?;
+    Ok((headers, Json(page.rides)))
+}
+
+async fn get_monthly_rides(
+    State(ride_repo): State<RideRepository>,
+    Path(year): Path<i32>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<Json<Vec<RideMonth<RideWithBike>>>> {
+    let (from, to) = year_range(year)?;
+    let filter = RideFilter {
+        from: Some(from),
+        to: Some(to),
+        ..Default::default()
+    };
+    let page = ride_repo.search_for_user(session.user_id, &filter).await?;
+    Ok(Json(RideMonth::group_year(year, page.rides)))
+}
+
+async fn search_rides(
+    State(ride_repo): State<RideRepository>,
+    Query(search): Query<RideSearchQuery>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<Json<Vec<RideSearchResult>>> {
+    let query = fts_query(&search.q)
+        .ok_or_else(|| AppError::BadRequest(String::from("Empty search query")))?;
+    let results = ride_repo
+        .full_text_search(
+            session.user_id,
+            &query,
+            search.limit.unwrap_or(DEFAULT_SEARCH_LIMIT),
+        )
+        .await?;
+    Ok(Json(results))
+}
+
+async fn bulk_rides(
+    State(pool): State<SqlitePool>,
+    Extension(session): Extension<SessionModel>,
+    Json(operations): Json<Vec<BulkOperation>>,
+) -> AppResult<(StatusCode, Json<BulkResponse>)> {
+    if operations.len() > MAX_BULK_OPERATIONS {
+        return Err(AppError::BadRequest(format!(
+            "At most {MAX_BULK_OPERATIONS} operations are allowed"
+        )));
+    }
+
+    let mut tx = pool.begin().await?;
+    let mut results = Vec::with_capacity(operations.len());
+    let mut failure = None;
+    for operation in &operations {
+        if failure.is_some() {
+            results.push(BulkResult::Skipped);
+            continue;
+        }
+
+        match apply_operation(&mut tx, session.user_id, operation).await {
+            Ok(result) => results.push(result),
+            Err(e) => {
+                results.push(BulkResult::Error {
+                    code: e.status_code().as_u16(),
+                    message: e.message(),
+                });
+                failure = Some(e.status_code());
+            }
+        }
+    }
+
+    let status = match failure {
+        Some(status) => {
+            tx.rollback().await?;
+            status
+        }
+        None => {
+            tx.commit().await?;
+            StatusCode::OK
+        }
+    };
+
+    let response = BulkResponse {
+        committed: failure.is_none(),
+        results,
+    };
+    Ok((status, Json(response)))
+}
+
+async fn apply_operation(
+    conn: &mut SqliteConnection,
+    user_id: i64,
+    operation: &BulkOperation,
+) -> AppResult<BulkResult> {
+    let actor = Actor::User(user_id);
+    let (ride, tags) = match operation {
+        BulkOperation::Create {
+            bike_id,
+            ride,
+            idempotency_key,
+        } => {
+            BikeRepository::assert_role_in(conn, *bike_id, user_id, BikeRole::Rider).await?;
+            let (created, is_new) = match idempotency_key {
+                Some(key) => {
+                    let key = valid_idempotency_key(key)?;
+                    RideRepository::create_idempotent_in(conn, *bike_id, Some(user_id), key, ride)
+                        .await?
+                }
+                None => (
+                    RideRepository::create_in(conn, *bike_id, Some(user_id), ride).await?,
+                    true,
+                ),
+            };
+            if is_new {
+                let history = (None, Some(&created));
+                record(conn, created.id, HistoryAction::Create, actor, history).await?;
+            }
+            (Some(created), None)
+        }
+        BulkOperation::Update {
+            ride_id,
+            ride,
+            version,
+        } => {
+            let before = editable_ride(conn, *ride_id, user_id).await?;
+            assert_version(Some(*version), before.version, &before)?;
+            let updated = RideRepository::update_in(conn, *ride_id, ride, before.version).await?;
+            let history = (Some(&before), Some(&updated));
+            record(conn, *ride_id, HistoryAction::Update, actor, history).await?;
+            (Some(updated), None)
+        }
+        BulkOperation::Delete { ride_id, version } => {
+            let before = editable_ride(conn, *ride_id, user_id).await?;
+            assert_version(Some(*version), before.version, &before)?;
+            RideRepository::delete_in(conn, *ride_id, before.version).await?;
+            let history = (Some(&before), None);
+            record(conn, *ride_id, HistoryAction::Delete, actor, history).await?;
+            (None, None)
+        }
+        BulkOperation::MoveToBike { ride_id, bike_id } => {
+            // Moving rides changes the totals of both bikes, which only their owners may do
+            let before = RideRepository::get_one_in(conn, *ride_id).await?;
+            BikeRepository::assert_role_in(conn, before.bike_id, user_id, BikeRole::Owner).await?;
+            BikeRepository::assert_role_in(conn, *bike_id, user_id, BikeRole::Owner).await?;
+            let moved = RideRepository::move_in(conn, *ride_id, *bike_id).await?;
+            let history = (Some(&before), Some(&moved));
+            record(conn, *ride_id, HistoryAction::Update, actor, history).await?;
+            (Some(moved), None)
+        }
+        BulkOperation::AddTag {
+            ride_id,
+            tag,
+            version,
+        } => {
+            let before = editable_ride(conn, *ride_id, user_id).await?;
+            assert_version(Some(*version), before.version, &before)?;
+            let before_tags = RideRepository::get_tags_in(conn, *ride_id).await?;
+            let (tagged, tags) =
+                RideRepository::add_tag_in(conn, *ride_id, before.version, tag).await?;
+            let before = TaggedRide {
+                ride: &before,
+                tags: &before_tags,
+            };
+            let after = TaggedRide {
+                ride: &tagged,
+                tags: &tags,
+            };
+            let history = (Some(&before), Some(&after));
+            record(conn, *ride_id, HistoryAction::Update, actor, history).await?;
+            (Some(tagged), Some(tags))
+        }
+    };
+
+    Ok(BulkResult::Ok { ride, tags })
+}
+
+async fn editable_ride(
+    conn: &mut SqliteConnection,
+    ride_id: i64,
+    user_id: i64,
+) -> AppResult<RideModel> {
+    let ride = RideRepository::get_one_in(conn, ride_id).await?;
+    let role = BikeRepository::assert_role_in(conn, ride.bike_id, user_id, BikeRole::Rider).await?;
+    role.assert_can_edit_ride(&ride, user_id)?;
+    Ok(ride)
+}
+
+async fn record<T: Serialize>(
+    conn: &mut SqliteConnection,
+    ride_id: i64,
+    action: HistoryAction,
+    actor: Actor,
+    (before, after): (Option<&T>, Option<&T>),
+) -> AppResult<()> {
+    HistoryRepository::record_in(
+        conn,
+        HistoryEntity::Ride,
+        ride_id,
+        action,
+        actor,
+        before,
+        after,
+    )
+    .await
+}
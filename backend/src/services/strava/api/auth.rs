@@ -41,8 +41,14 @@ impl StravaApiWithAuth {
         filter: &ActivityFilter,
     ) -> anyhow::Result<Vec<SummaryActivity>> {
         let query = [
-            ("before", filter.before.map(|x| x.timestamp().to_string())),
-            ("after", filter.after.map(|x| x.timestamp().to_string())),
+            (
+                "before",
+                filter.before.map(|x| x.and_utc().timestamp().to_string()),
+            ),
+            (
+                "after",
+                filter.after.map(|x| x.and_utc().timestamp().to_string()),
+            ),
             ("page", Some(filter.page.to_string())),
             ("per_page", Some(filter.per_page.to_string())),
         ]
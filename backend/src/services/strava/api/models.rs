@@ -73,13 +73,21 @@ impl From<String> for SportType {
     }
 }
 
+#[derive(Deserialize)]
+pub struct PolylineMap {
+    pub summary_polyline: Option<String>,
+}
+
 #[derive(Deserialize)]
 pub struct SummaryActivity {
     pub id: i64,
     pub name: String,
     #[serde(rename = "distance")]
     pub distance_meters: f64,
+    /// Moving time in seconds
+    pub moving_time: Option<i64>,
     pub sport_type: SportType,
     pub start_date_local: DateTime<Utc>,
     pub gear_id: Option<String>,
+    pub map: Option<PolylineMap>,
 }
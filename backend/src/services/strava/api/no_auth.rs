@@ -1,4 +1,4 @@
-use chrono::NaiveDateTime;
+use chrono::DateTime;
 use reqwest::Client;
 
 use crate::{config::StravaConfig, services::strava::models::StravaModel};
@@ -45,7 +45,9 @@ impl StravaApiNoAuth {
             .json::<IssueTokenResponse>()
             .await?;
 
-        let expires_at = NaiveDateTime::from_timestamp_opt(response.expires_at, 0).unwrap();
+        let expires_at = DateTime::from_timestamp(response.expires_at, 0)
+            .unwrap()
+            .naive_utc();
         Ok(StravaModel {
             user_id: user_id.into(),
             strava_id: response.athlete.id,
@@ -53,7 +55,7 @@ impl StravaApiNoAuth {
                 "{} {}",
                 response.athlete.firstname, response.athlete.lastname
             ),
-            last_sync: NaiveDateTime::from_timestamp_opt(0, 0).unwrap(),
+            last_sync: DateTime::UNIX_EPOCH.naive_utc(),
             access_token: response.access_token,
             refresh_token: response.refresh_token,
             expires_at,
@@ -76,7 +78,9 @@ impl StravaApiNoAuth {
             .json::<RefreshTokenResponse>()
             .await?;
 
-        let expires_at = NaiveDateTime::from_timestamp_opt(response.expires_at, 0).unwrap();
+        let expires_at = DateTime::from_timestamp(response.expires_at, 0)
+            .unwrap()
+            .naive_utc();
         Ok(StravaModel {
             user_id: model.user_id,
             strava_id: model.strava_id,
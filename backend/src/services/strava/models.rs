@@ -94,3 +94,11 @@ pub struct OAuthUrl {
 pub struct StravaLinkPartial {
     pub last_sync: NaiveDateTime,
 }
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StravaBikeImport {
+    /// Imports all bikes of the athlete when missing
+    #[serde(default)]
+    pub gear_ids: Option<Vec<String>>,
+}
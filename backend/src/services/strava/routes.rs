@@ -11,6 +11,7 @@ use axum::routing::put;
 use chrono::{Duration, NaiveDateTime, Utc};
 use reqwest::StatusCode;
 use serde::Deserialize;
+use sqlx::SqlitePool;
 use tokio::sync::Mutex;
 use uuid::Uuid;
 
@@ -18,12 +19,22 @@ use crate::{
     services::{
         auth::models::SessionModel,
         bikes::{
+            models::{BikeModel, BikePartial},
             repository::BikeRepository,
-            rides::{models::RidePartial, repository::RideRepository},
+            rides::{
+                models::RidePartial,
+                repository::RideRepository,
+                tracks::{models::TrackSource, repository::TrackRepository},
+            },
+        },
+        history::{
+            models::{Actor, HistoryAction, HistoryEntity},
+            repository::HistoryRepository,
         },
     },
     utility::{
         error::{AppError, AppResult},
+        geometry::decode_polyline,
         state::AppState,
     },
 };
@@ -32,14 +43,22 @@ use crate::services::strava::models::{OAuthUrl, StravaLinkPartial};
 use super::{
     api::models::{ActivityFilter, SummaryGear},
     extractor::Strava,
-    models::{StravaLink, StravaModel},
+    models::{StravaBikeImport, StravaLink, StravaModel},
     repository::StravaRepository,
 };
 
 const SCOPES: &[&str] = &["read_all", "profile:read_all", "activity:read_all"];
 const TIMEOUT_SECONDS: i64 = 10 * 60;
 
-static STATES: LazyLock<Mutex<HashMap<Uuid, (i64, NaiveDateTime)>>> =
+/// OAuth flow started by the user, waiting for the redirect from Strava.
+struct PendingLink {
+    user_id: i64,
+    /// Imports bikes of the athlete once the account gets linked
+    import_bikes: bool,
+    created: NaiveDateTime,
+}
+
+static STATES: LazyLock<Mutex<HashMap<Uuid, PendingLink>>> =
     LazyLock::new(|| Mutex::new(HashMap::new()));
 
 async fn clear_states() {
@@ -49,8 +68,8 @@ async fn clear_states() {
     let now = Utc::now().naive_utc();
     let expired: Vec<_> = states
         .iter()
-        .filter_map(|(state, (_, created))| {
-            if now.signed_duration_since(*created).num_seconds() > TIMEOUT_SECONDS {
+        .filter_map(|(state, pending)| {
+            if now.signed_duration_since(pending.created).num_seconds() > TIMEOUT_SECONDS {
                 Some(*state)
             } else {
                 None
@@ -74,6 +93,14 @@ async fn keep_fresh_token(model: StravaModel, repo: &StravaRepository, api: &Str
     Ok(model)
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LinkQuery {
+    /// Imports bikes of the athlete right after linking
+    #[serde(default)]
+    import_bikes: bool,
+}
+
 #[derive(Deserialize)]
 struct OAuthQuery {
     state: Uuid,
@@ -92,12 +119,14 @@ pub fn router_with_auth() -> Router<AppState> {
         .route("/link", delete(unlink))
         .route("/", get(get_link))
         .route("/bikes", get(bikes))
+        .route("/bikes", post(import_bikes))
         .route("/", post(sync))
         .route("/", put(update_link))
 }
 
 async fn oauth(
     Strava(config, _): Strava,
+    Query(query): Query<LinkQuery>,
     Extension(session): Extension<SessionModel>,
 ) -> AppResult<(StatusCode, Json<OAuthUrl>)> {
     let state = Uuid::new_v4();
@@ -110,7 +139,14 @@ async fn oauth(
     clear_states().await;
     let states = &(*STATES);
     let mut states = states.lock().await;
-    states.insert(state, (user_id, now));
+    states.insert(
+        state,
+        PendingLink {
+            user_id,
+            import_bikes: query.import_bikes,
+            created: now,
+        },
+    );
 
     Ok((StatusCode::OK, Json(OAuthUrl { url: oauth })))
 }
@@ -118,6 +154,8 @@ async fn oauth(
 async fn redirect(
     Strava(_, api): Strava,
     State(repo): State<StravaRepository>,
+    State(bikes): State<BikeRepository>,
+    State(pool): State<SqlitePool>,
     Query(query): Query<OAuthQuery>,
 ) -> AppResult<(StatusCode, HeaderMap)> {
     let scopes = query
@@ -135,12 +173,21 @@ async fn redirect(
     let states = &(*STATES);
     let mut states = states.lock().await;
 
-    let (user_id, _) = states
+    let pending = states
         .remove(&query.state)
         .ok_or_else(|| anyhow!("Invalid state"))?;
+    drop(states);
+
+    let model = api.issue_token(&query.code, pending.user_id).await?;
+    repo.create(model.clone()).await?;
 
-    let model = api.issue_token(&query.code, user_id).await?;
-    repo.create(model).await?;
+    if pending.import_bikes {
+        // The account is linked already, failed import can be retried manually
+        let imported = import_gear(api, &model, &bikes, &pool, None).await;
+        if let Err(e) = imported {
+            println!("Failed to import Strava bikes: {}", e.detailed());
+        }
+    }
 
     let mut headers = HeaderMap::new();
     headers.insert("Location", "/settings".parse().unwrap());
@@ -184,11 +231,90 @@ async fn bikes(
     Ok(Json(athlete.bikes))
 }
 
+/// Creates bikes for the selected or all bikes of the athlete,
+/// skipping gear that is already linked to a bike.
+async fn import_bikes(
+    Extension(session): Extension<SessionModel>,
+    State(repo): State<StravaRepository>,
+    State(bikes): State<BikeRepository>,
+    State(pool): State<SqlitePool>,
+    Strava(_, api): Strava,
+    Json(payload): Json<StravaBikeImport>,
+) -> AppResult<(StatusCode, Json<Vec<BikeModel>>)> {
+    let link = repo
+        .try_get(session.user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Strava account not linked".to_string()))?;
+
+    let link = keep_fresh_token(link, &repo, &api).await?;
+    let models = import_gear(api, &link, &bikes, &pool, payload.gear_ids).await?;
+    Ok((StatusCode::CREATED, Json(models)))
+}
+
+async fn import_gear(
+    api: StravaApiNoAuth,
+    link: &StravaModel,
+    bikes: &BikeRepository,
+    pool: &SqlitePool,
+    gear_ids: Option<Vec<String>>,
+) -> AppResult<Vec<BikeModel>> {
+    let api = api.with_auth(link)?;
+    let athlete = api.get_athlete().await?;
+
+    let gear = match gear_ids {
+        None => athlete.bikes,
+        Some(gear_ids) => {
+            if let Some(unknown) = gear_ids
+                .iter()
+                .find(|id| !athlete.bikes.iter().any(|gear| &gear.id == *id))
+            {
+                return Err(AppError::BadRequest(format!(
+                    "Unknown Strava gear {unknown}"
+                )));
+            }
+
+            athlete
+                .bikes
+                .into_iter()
+                .filter(|gear| gear_ids.contains(&gear.id))
+                .collect()
+        }
+    };
+
+    let linked = bikes.get_strava_gears(link.user_id).await?;
+    let mut models = Vec::new();
+    for gear in gear.into_iter().filter(|gear| !linked.contains(&gear.id)) {
+        let new = BikePartial {
+            name: gear.name,
+            strava_gear: Some(gear.id),
+            ..Default::default()
+        };
+        let mut tx = pool.begin().await?;
+        let model = BikeRepository::create_in(&mut tx, link.user_id, &new).await?;
+        HistoryRepository::record_in(
+            &mut tx,
+            HistoryEntity::Bike,
+            model.id,
+            HistoryAction::Create,
+            Actor::StravaSync(link.user_id),
+            None,
+            Some(&model),
+        )
+        .await?;
+        tx.commit().await?;
+        models.push(model);
+    }
+
+    Ok(models)
+}
+
 async fn sync(
     Extension(session): Extension<SessionModel>,
     State(repo): State<StravaRepository>,
     State(rides): State<RideRepository>,
     State(bikes): State<BikeRepository>,
+    State(tracks): State<TrackRepository>,
+    State(pool): State<SqlitePool>,
     Strava(_, api): Strava,
 ) -> AppResult<(StatusCode, String)> {
     let link = repo
@@ -233,8 +359,14 @@ async fn sync(
                 distance: ride.distance_meters / 1000.0,
                 description: Some(ride.name),
                 strava_ride: Some(ride.id),
+                moving_time: ride.moving_time,
             };
-            
+            let track = ride
+                .map
+                .and_then(|map| map.summary_polyline)
+                .and_then(|polyline| decode_polyline(&polyline).ok())
+                .filter(|points| !points.is_empty());
+
             for bike_id in bike_ids {
                 let existing = rides
                     .try_get_by_strava_ride_including_deleted(*bike_id, ride.id)
@@ -243,7 +375,24 @@ async fn sync(
                     continue;
                 }
 
-                rides.create(*bike_id, &new).await?;
+                let mut tx = pool.begin().await?;
+                let created =
+                    RideRepository::create_in(&mut tx, *bike_id, Some(session.user_id), &new)
+                        .await?;
+                HistoryRepository::record_in(
+                    &mut tx,
+                    HistoryEntity::Ride,
+                    created.id,
+                    HistoryAction::Create,
+                    Actor::StravaSync(session.user_id),
+                    None,
+                    Some(&created),
+                )
+                .await?;
+                tx.commit().await?;
+                if let Some(points) = &track {
+                    tracks.save(created.id, TrackSource::Strava, points).await?;
+                }
             }
         }
 
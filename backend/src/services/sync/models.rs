@@ -0,0 +1,33 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::services::bikes::models::BikeModel;
+use crate::services::bikes::rides::models::RideModel;
+use crate::utility::db_format::{format_timestamp, parse_timestamp};
+
+#[derive(Debug, Deserialize)]
+pub struct ChangesQuery {
+    pub since: Option<String>,
+}
+
+/// Bikes and rides changed since the cursor, deleted ones have `deletedAt` set.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncChanges {
+    pub bikes: Vec<BikeModel>,
+    pub rides: Vec<RideModel>,
+    /// Bikes the user is no longer a member of, to be dropped with their rides
+    pub removed_bikes: Vec<i64>,
+    /// Cursor to pass as `since` on the next sync. Records changed
+    /// exactly at the cursor are returned again.
+    pub cursor: String,
+}
+
+pub fn encode_cursor(timestamp: &NaiveDateTime) -> String {
+    hex::encode(format_timestamp(timestamp))
+}
+
+pub fn decode_cursor(cursor: &str) -> anyhow::Result<NaiveDateTime> {
+    let decoded = String::from_utf8(hex::decode(cursor)?)?;
+    Ok(parse_timestamp(&decoded)?)
+}
@@ -0,0 +1,58 @@
+use axum::extract::{Query, State};
+use axum::routing::get;
+use axum::{Extension, Json, Router};
+use chrono::DateTime;
+
+use crate::services::auth::models::SessionModel;
+use crate::services::bikes::members::repository::MemberRepository;
+use crate::services::bikes::repository::BikeRepository;
+use crate::services::bikes::rides::repository::RideRepository;
+use crate::utility::error::{AppError, AppResult};
+use crate::utility::state::AppState;
+
+use super::models::{decode_cursor, encode_cursor, ChangesQuery, SyncChanges};
+
+pub fn router_with_auth() -> Router<AppState> {
+    Router::new().route("/changes", get(get_changes))
+}
+
+async fn get_changes(
+    State(bike_repo): State<BikeRepository>,
+    State(ride_repo): State<RideRepository>,
+    State(member_repo): State<MemberRepository>,
+    Query(query): Query<ChangesQuery>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<Json<SyncChanges>> {
+    let since = match &query.since {
+        Some(cursor) => decode_cursor(cursor)
+            .map_err(|e| AppError::BadRequest(format!("Invalid cursor: {e}")))?,
+        None => DateTime::UNIX_EPOCH.naive_utc(),
+    };
+
+    let bikes = bike_repo.get_changed_since(session.user_id, &since).await?;
+    let rides = ride_repo.get_changed_since(session.user_id, &since).await?;
+    let access = member_repo
+        .get_access_changes(session.user_id, &since)
+        .await?;
+
+    let latest = bikes
+        .iter()
+        .map(|bike| bike.updated_at)
+        .chain(rides.iter().map(|ride| ride.updated_at))
+        .chain(access.iter().map(|change| change.changed_at))
+        .max()
+        .unwrap_or(since);
+
+    let removed_bikes = access
+        .iter()
+        .filter(|change| change.removed)
+        .map(|change| change.bike_id)
+        .collect();
+
+    Ok(Json(SyncChanges {
+        bikes,
+        rides,
+        removed_bikes,
+        cursor: encode_cursor(&latest),
+    }))
+}
@@ -0,0 +1,149 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::utility::{db_extensions::Model, db_format::parse_date_time};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TransferStatus {
+    Pending,
+    Accepted,
+    Declined,
+    Cancelled,
+}
+
+impl TransferStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransferStatus::Pending => "pending",
+            TransferStatus::Accepted => "accepted",
+            TransferStatus::Declined => "declined",
+            TransferStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl FromStr for TransferStatus {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(TransferStatus::Pending),
+            "accepted" => Ok(TransferStatus::Accepted),
+            "declined" => Ok(TransferStatus::Declined),
+            "cancelled" => Ok(TransferStatus::Cancelled),
+            _ => Err(anyhow!("Unknown transfer status {s}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TransferRaw {
+    pub id: i64,
+    pub bike_id: i64,
+    pub from_user_id: i64,
+    pub to_user_id: i64,
+    pub keep_snapshot: bool,
+    pub status: String,
+    pub created_at: String,
+    pub resolved_at: Option<String>,
+    pub bike_name: String,
+    pub from_username: String,
+    pub to_username: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferModel {
+    pub id: i64,
+    pub bike_id: i64,
+    pub from_user_id: i64,
+    pub to_user_id: i64,
+    pub keep_snapshot: bool,
+    pub status: TransferStatus,
+    pub created_at: NaiveDateTime,
+    pub resolved_at: Option<NaiveDateTime>,
+    pub bike_name: String,
+    pub from_username: String,
+    pub to_username: String,
+}
+
+impl Model<TransferRaw> for TransferModel {}
+
+impl TryFrom<TransferRaw> for TransferModel {
+    type Error = anyhow::Error;
+    fn try_from(raw: TransferRaw) -> Result<Self, Self::Error> {
+        Ok(TransferModel {
+            id: raw.id,
+            bike_id: raw.bike_id,
+            from_user_id: raw.from_user_id,
+            to_user_id: raw.to_user_id,
+            keep_snapshot: raw.keep_snapshot,
+            status: raw.status.parse()?,
+            created_at: parse_date_time(&raw.created_at)?,
+            resolved_at: raw.resolved_at.map(|s| parse_date_time(&s)).transpose()?,
+            bike_name: raw.bike_name,
+            from_username: raw.from_username,
+            to_username: raw.to_username,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferPartial {
+    pub bike_id: i64,
+    pub username: String,
+    /// Keep the totals of the previous owner after the transfer
+    #[serde(default)]
+    pub keep_snapshot: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct SnapshotRaw {
+    pub id: i64,
+    pub user_id: i64,
+    pub bike_id: Option<i64>,
+    pub bike_name: String,
+    pub rides: i64,
+    pub distance: f64,
+    pub moving_time: i64,
+    pub maintenance_cost: f64,
+    pub created_at: String,
+}
+
+/// Read-only totals of the previous owner, taken when the bike was transferred.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotModel {
+    pub id: i64,
+    pub user_id: i64,
+    pub bike_id: Option<i64>,
+    pub bike_name: String,
+    pub rides: i64,
+    pub distance: f64,
+    pub moving_time: i64,
+    pub maintenance_cost: f64,
+    pub created_at: NaiveDateTime,
+}
+
+impl Model<SnapshotRaw> for SnapshotModel {}
+
+impl TryFrom<SnapshotRaw> for SnapshotModel {
+    type Error = anyhow::Error;
+    fn try_from(raw: SnapshotRaw) -> Result<Self, Self::Error> {
+        Ok(SnapshotModel {
+            id: raw.id,
+            user_id: raw.user_id,
+            bike_id: raw.bike_id,
+            bike_name: raw.bike_name,
+            rides: raw.rides,
+            distance: raw.distance,
+            moving_time: raw.moving_time,
+            maintenance_cost: raw.maintenance_cost,
+            created_at: parse_date_time(&raw.created_at)?,
+        })
+    }
+}
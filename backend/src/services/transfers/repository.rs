@@ -0,0 +1,228 @@
+use chrono::Utc;
+use sqlx::SqlitePool;
+
+use crate::services::bikes::members::models::BikeRole;
+use crate::services::bikes::members::repository::MemberRepository;
+use crate::services::bikes::repository::BikeRepository;
+use crate::services::history::models::{Actor, HistoryAction, HistoryEntity};
+use crate::services::history::repository::HistoryRepository;
+use crate::utility::{
+    db_extensions::IntoModels,
+    db_format::format_date_time,
+    error::{AppError, AppResult},
+};
+
+use super::models::{SnapshotModel, SnapshotRaw, TransferModel, TransferRaw, TransferStatus};
+
+#[derive(Clone)]
+pub struct TransferRepository(SqlitePool);
+
+impl TransferRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self(pool)
+    }
+
+    /// Pending transfers sent or received by the user.
+    pub async fn get_pending(&self, user_id: i64) -> AppResult<Vec<TransferModel>> {
+        let models = sqlx::query_as!(
+            TransferRaw,
+            r#"SELECT bike_transfers.id AS "id!", bike_transfers.bike_id AS "bike_id!", bike_transfers.from_user_id AS "from_user_id!", bike_transfers.to_user_id AS "to_user_id!", bike_transfers.keep_snapshot AS "keep_snapshot!: bool", bike_transfers.status AS "status!", bike_transfers.created_at AS "created_at!", bike_transfers.resolved_at, bikes.name AS "bike_name!", senders.username AS "from_username!", recipients.username AS "to_username!" FROM bike_transfers JOIN bikes ON bikes.id = bike_transfers.bike_id JOIN users AS senders ON senders.id = bike_transfers.from_user_id JOIN users AS recipients ON recipients.id = bike_transfers.to_user_id WHERE bike_transfers.status = 'pending' AND (bike_transfers.from_user_id = ?1 OR bike_transfers.to_user_id = ?1) ORDER BY bike_transfers.id"#,
+            user_id
+        )
+        .fetch_all(&self.0)
+        .await?
+        .into_models()?;
+
+        Ok(models)
+    }
+
+    pub async fn get_one(&self, transfer_id: i64) -> AppResult<TransferModel> {
+        let model = sqlx::query_as!(
+            TransferRaw,
+            r#"SELECT bike_transfers.id AS "id!", bike_transfers.bike_id AS "bike_id!", bike_transfers.from_user_id AS "from_user_id!", bike_transfers.to_user_id AS "to_user_id!", bike_transfers.keep_snapshot AS "keep_snapshot!: bool", bike_transfers.status AS "status!", bike_transfers.created_at AS "created_at!", bike_transfers.resolved_at, bikes.name AS "bike_name!", senders.username AS "from_username!", recipients.username AS "to_username!" FROM bike_transfers JOIN bikes ON bikes.id = bike_transfers.bike_id JOIN users AS senders ON senders.id = bike_transfers.from_user_id JOIN users AS recipients ON recipients.id = bike_transfers.to_user_id WHERE bike_transfers.id = ?"#,
+            transfer_id
+        )
+        .fetch_optional(&self.0)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("No transfer found with id {transfer_id}")))?
+        .try_into()?;
+
+        Ok(model)
+    }
+
+    pub async fn create(
+        &self,
+        bike_id: i64,
+        from_user_id: i64,
+        to_user_id: i64,
+        keep_snapshot: bool,
+    ) -> AppResult<TransferModel> {
+        let status = TransferStatus::Pending.as_str();
+        let created_at = format_date_time(&Utc::now().naive_utc());
+        let result = sqlx::query!(
+            "INSERT INTO bike_transfers (bike_id, from_user_id, to_user_id, keep_snapshot, status, created_at) VALUES (?, ?, ?, ?, ?, ?) ON CONFLICT DO NOTHING",
+            bike_id,
+            from_user_id,
+            to_user_id,
+            keep_snapshot,
+            status,
+            created_at
+        )
+        .execute(&self.0)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::Conflict(String::from(
+                "Bike already has a pending transfer",
+            )));
+        }
+
+        self.get_one(result.last_insert_rowid()).await
+    }
+
+    /// Resolves a pending transfer without moving the bike.
+    pub async fn resolve(
+        &self,
+        transfer_id: i64,
+        status: TransferStatus,
+    ) -> AppResult<TransferModel> {
+        let status = status.as_str();
+        let now = format_date_time(&Utc::now().naive_utc());
+        let result = sqlx::query!(
+            "UPDATE bike_transfers SET status = ?, resolved_at = ? WHERE id = ? AND status = 'pending'",
+            status,
+            now,
+            transfer_id
+        )
+        .execute(&self.0)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::Conflict(String::from(
+                "Transfer is no longer pending",
+            )));
+        }
+
+        self.get_one(transfer_id).await
+    }
+
+    /// Hands the bike over to the recipient together with its rides, maintenance
+    /// and installed components, optionally keeping the totals of the previous owner.
+    pub async fn accept(&self, transfer: &TransferModel) -> AppResult<TransferModel> {
+        let accepted = TransferStatus::Accepted.as_str();
+        let now = format_date_time(&Utc::now().naive_utc());
+        let mut tx = self.0.begin().await?;
+
+        let result = sqlx::query!(
+            "UPDATE bike_transfers SET status = ?, resolved_at = ? WHERE id = ? AND status = 'pending'",
+            accepted,
+            now,
+            transfer.id
+        )
+        .execute(&mut *tx)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(AppError::Conflict(String::from(
+                "Transfer is no longer pending",
+            )));
+        }
+
+        let before = BikeRepository::get_one_in(&mut tx, transfer.bike_id).await?;
+        if before.owner_id != transfer.from_user_id {
+            return Err(AppError::Conflict(String::from(
+                "Bike is no longer owned by the sender",
+            )));
+        }
+
+        if transfer.keep_snapshot {
+            sqlx::query!(
+                "INSERT INTO bike_snapshots (user_id, bike_id, bike_name, rides, distance, moving_time, maintenance_cost, created_at) SELECT ?, ?, ?, COUNT(*), COALESCE(SUM(distance), 0.0), COALESCE(SUM(moving_time), 0), (SELECT COALESCE(SUM(cost), 0.0) FROM maintenance WHERE bike_id = ?), ? FROM rides WHERE bike_id = ? AND rider_id = ? AND deleted_at IS NULL",
+                transfer.from_user_id,
+                transfer.bike_id,
+                before.name,
+                transfer.bike_id,
+                now,
+                transfer.bike_id,
+                transfer.from_user_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        // Strava gear belongs to the account of the previous owner
+        sqlx::query!(
+            "UPDATE bikes SET owner_id = ?, strava_gear = NULL WHERE id = ?",
+            transfer.to_user_id,
+            transfer.bike_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        MemberRepository::set_in(
+            &mut tx,
+            transfer.bike_id,
+            transfer.to_user_id,
+            BikeRole::Owner,
+        )
+        .await?;
+        sqlx::query!(
+            "DELETE FROM bike_members WHERE bike_id = ? AND user_id = ?",
+            transfer.bike_id,
+            transfer.from_user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE reminders SET owner_id = ? WHERE owner_id = ? AND component_id IN (SELECT component_id FROM component_installations WHERE bike_id = ? AND removed_at IS NULL)",
+            transfer.to_user_id,
+            transfer.from_user_id,
+            transfer.bike_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            "UPDATE components SET owner_id = ? WHERE owner_id = ? AND id IN (SELECT component_id FROM component_installations WHERE bike_id = ? AND removed_at IS NULL)",
+            transfer.to_user_id,
+            transfer.from_user_id,
+            transfer.bike_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            "DELETE FROM reminders WHERE owner_id = ? AND bike_id = ?",
+            transfer.from_user_id,
+            transfer.bike_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let after = BikeRepository::get_one_in(&mut tx, transfer.bike_id).await?;
+        HistoryRepository::record_in(
+            &mut tx,
+            HistoryEntity::Bike,
+            transfer.bike_id,
+            HistoryAction::Update,
+            Actor::User(transfer.to_user_id),
+            Some(&before),
+            Some(&after),
+        )
+        .await?;
+        tx.commit().await?;
+
+        self.get_one(transfer.id).await
+    }
+
+    pub async fn get_snapshots(&self, user_id: i64) -> AppResult<Vec<SnapshotModel>> {
+        let models = sqlx::query_as!(
+            SnapshotRaw,
+            r#"SELECT id AS "id!", user_id, bike_id, bike_name, rides, distance, moving_time, maintenance_cost, created_at FROM bike_snapshots WHERE user_id = ? ORDER BY created_at DESC, id DESC"#,
+            user_id
+        )
+        .fetch_all(&self.0)
+        .await?
+        .into_models()?;
+
+        Ok(models)
+    }
+}
@@ -0,0 +1,134 @@
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::routing::{delete, get, post};
+use axum::{Extension, Json, Router};
+
+use crate::services::auth::models::SessionModel;
+use crate::services::bikes::members::models::BikeRole;
+use crate::services::bikes::repository::BikeRepository;
+use crate::services::users::repository::UserRepository;
+use crate::utility::error::{AppError, AppResult};
+use crate::utility::state::AppState;
+
+use super::models::{SnapshotModel, TransferModel, TransferPartial, TransferStatus};
+use super::repository::TransferRepository;
+
+pub fn router_with_auth() -> Router<AppState> {
+    Router::new()
+        .route("/", get(get_transfers))
+        .route("/", post(create_transfer))
+        .route("/snapshots", get(get_snapshots))
+        .route("/{id}", get(get_transfer))
+        .route("/{id}", delete(cancel_transfer))
+        .route("/{id}/accept", post(accept_transfer))
+        .route("/{id}/decline", post(decline_transfer))
+}
+
+/// Pending transfers sent or received by the user.
+async fn get_transfers(
+    State(transfer_repo): State<TransferRepository>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<Json<Vec<TransferModel>>> {
+    let models = transfer_repo.get_pending(session.user_id).await?;
+    Ok(Json(models))
+}
+
+async fn create_transfer(
+    State(bike_repo): State<BikeRepository>,
+    State(transfer_repo): State<TransferRepository>,
+    State(user_repo): State<UserRepository>,
+    Extension(session): Extension<SessionModel>,
+    Json(payload): Json<TransferPartial>,
+) -> AppResult<(StatusCode, Json<TransferModel>)> {
+    bike_repo
+        .assert_role(payload.bike_id, session.user_id, BikeRole::Owner)
+        .await?;
+    let bike = bike_repo.get_one(payload.bike_id).await?;
+    if bike.owner_id != session.user_id {
+        return Err(AppError::Forbidden);
+    }
+
+    let recipient = user_repo
+        .try_get_by_username(&payload.username)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("No user {}", payload.username)))?;
+    if recipient.id == session.user_id {
+        return Err(AppError::BadRequest(String::from(
+            "Bike cannot be transferred to its owner",
+        )));
+    }
+
+    let model = transfer_repo
+        .create(
+            payload.bike_id,
+            session.user_id,
+            recipient.id,
+            payload.keep_snapshot,
+        )
+        .await?;
+    Ok((StatusCode::CREATED, Json(model)))
+}
+
+async fn get_transfer(
+    State(transfer_repo): State<TransferRepository>,
+    Path(id): Path<i64>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<Json<TransferModel>> {
+    let model = transfer_repo.get_one(id).await?;
+    if model.from_user_id != session.user_id && model.to_user_id != session.user_id {
+        return Err(AppError::Forbidden);
+    }
+
+    Ok(Json(model))
+}
+
+async fn cancel_transfer(
+    State(transfer_repo): State<TransferRepository>,
+    Path(id): Path<i64>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<Json<TransferModel>> {
+    let transfer = transfer_repo.get_one(id).await?;
+    if transfer.from_user_id != session.user_id {
+        return Err(AppError::Forbidden);
+    }
+
+    let model = transfer_repo.resolve(id, TransferStatus::Cancelled).await?;
+    Ok(Json(model))
+}
+
+async fn accept_transfer(
+    State(transfer_repo): State<TransferRepository>,
+    Path(id): Path<i64>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<Json<TransferModel>> {
+    let transfer = transfer_repo.get_one(id).await?;
+    if transfer.to_user_id != session.user_id {
+        return Err(AppError::Forbidden);
+    }
+
+    let model = transfer_repo.accept(&transfer).await?;
+    Ok(Json(model))
+}
+
+async fn decline_transfer(
+    State(transfer_repo): State<TransferRepository>,
+    Path(id): Path<i64>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<Json<TransferModel>> {
+    let transfer = transfer_repo.get_one(id).await?;
+    if transfer.to_user_id != session.user_id {
+        return Err(AppError::Forbidden);
+    }
+
+    let model = transfer_repo.resolve(id, TransferStatus::Declined).await?;
+    Ok(Json(model))
+}
+
+/// Totals kept from bikes the user transferred to someone else.
+async fn get_snapshots(
+    State(transfer_repo): State<TransferRepository>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<Json<Vec<SnapshotModel>>> {
+    let models = transfer_repo.get_snapshots(session.user_id).await?;
+    Ok(Json(models))
+}
@@ -0,0 +1,39 @@
+use std::time::Duration;
+
+use chrono::{TimeDelta, Utc};
+
+use crate::services::bikes::photos::storage::PhotoStorage;
+
+use super::repository::TrashRepository;
+
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Periodically purges rides and bikes which have been in the trash longer than the retention,
+/// together with the photo files of the purged bikes.
+pub async fn run(trash_repo: TrashRepository, storage: PhotoStorage, retention_days: i64) {
+    let mut interval = tokio::time::interval(CLEANUP_INTERVAL);
+    loop {
+        interval.tick().await;
+
+        let cutoff = Utc::now().naive_utc() - TimeDelta::days(retention_days);
+        let purged = match trash_repo.purge(&cutoff).await {
+            Ok(purged) => purged,
+            Err(e) => {
+                println!("Failed to purge trash: {}", e.detailed());
+                continue;
+            }
+        };
+
+        if purged.rides > 0 || purged.bikes > 0 {
+            println!(
+                "Purged {} rides and {} bikes from trash",
+                purged.rides, purged.bikes
+            );
+        }
+        for photo_id in &purged.photos {
+            if let Err(e) = storage.delete(photo_id).await {
+                println!("Failed to delete photo {photo_id}: {}", e.detailed());
+            }
+        }
+    }
+}
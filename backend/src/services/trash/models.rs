@@ -0,0 +1,8 @@
+/// Rides and bikes permanently deleted from the trash.
+#[derive(Debug)]
+pub struct Purged {
+    pub rides: u64,
+    pub bikes: u64,
+    /// Photos of the purged bikes, their files are left to delete
+    pub photos: Vec<String>,
+}
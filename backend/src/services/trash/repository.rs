@@ -0,0 +1,197 @@
+use chrono::NaiveDateTime;
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::services::bikes::members::models::BikeRole;
+use crate::services::bikes::models::{BikeModel, BikeRaw};
+use crate::services::bikes::rides::models::{RideModel, RideRaw, RideWithBike, RideWithBikeRaw};
+use crate::utility::{
+    db_extensions::IntoModels,
+    db_format::format_date_time,
+    error::{AppError, AppResult},
+};
+
+use super::models::Purged;
+
+#[derive(Clone)]
+pub struct TrashRepository(SqlitePool);
+
+impl TrashRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self(pool)
+    }
+
+    /// Deleted bikes the user can restore as their owner.
+    pub async fn get_bikes(&self, user_id: i64) -> AppResult<Vec<BikeModel>> {
+        let models = sqlx::query_as!(
+            BikeRaw,
+            r#"SELECT id AS "id!", name, description, deleted_at, color, owner_id, strava_gear, updated_at, version, initial_distance, initial_distance_date, bike_type, frame_size, wheel_size, weight, model_year, purchase_date, purchase_price, currency, photo, retired_at FROM bikes WHERE id IN (SELECT bike_id FROM bike_members WHERE user_id = ? AND role = 'owner') AND deleted_at IS NOT NULL ORDER BY deleted_at DESC"#,
+            user_id
+        )
+        .fetch_all(&self.0)
+        .await?
+        .into_models()?;
+
+        Ok(models)
+    }
+
+    /// Deleted rides the user can restore, all rides of owned bikes and own rides of ridden bikes.
+    pub async fn get_rides(&self, user_id: i64) -> AppResult<Vec<RideWithBike>> {
+        let models = sqlx::query_as::<_, RideWithBikeRaw>(
+            "SELECT rides.*, bikes.name AS bike_name, bikes.color AS bike_color FROM rides JOIN bikes ON bikes.id = rides.bike_id JOIN bike_members ON bike_members.bike_id = rides.bike_id AND bike_members.user_id = ?1 WHERE rides.deleted_at IS NOT NULL AND (bike_members.role = 'owner' OR (bike_members.role = 'rider' AND rides.rider_id = ?1)) ORDER BY rides.deleted_at DESC",
+        )
+        .bind(user_id)
+        .fetch_all(&self.0)
+        .await?
+        .into_models()?;
+
+        Ok(models)
+    }
+
+    pub async fn get_bike(&self, bike_id: i64) -> AppResult<BikeModel> {
+        let model = sqlx::query_as!(
+            BikeRaw,
+            "SELECT * FROM bikes WHERE id = ? AND deleted_at IS NOT NULL",
+            bike_id
+        )
+        .fetch_optional(&self.0)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("No deleted bike found with id {bike_id}")))?
+        .try_into()?;
+
+        Ok(model)
+    }
+
+    /// Rides trashed together with the bike, not those deleted before it.
+    pub async fn get_rides_deleted_with_bike(&self, bike_id: i64) -> AppResult<Vec<RideModel>> {
+        let models = sqlx::query_as!(
+            RideRaw,
+            r#"SELECT id AS "id!", date AS "date!", distance AS "distance!", description, deleted_at, bike_id AS "bike_id!", strava_ride, updated_at AS "updated_at!", version AS "version!", moving_time, rider_id FROM rides WHERE bike_id = ?1 AND deleted_at >= (SELECT deleted_at FROM bikes WHERE id = ?1)"#,
+            bike_id
+        )
+        .fetch_all(&self.0)
+        .await?
+        .into_models()?;
+
+        Ok(models)
+    }
+
+    pub async fn assert_can_restore_bike(&self, bike_id: i64, user_id: i64) -> AppResult<()> {
+        let bike = sqlx::query!(
+            "SELECT bike_members.role FROM bikes LEFT JOIN bike_members ON bike_members.bike_id = bikes.id AND bike_members.user_id = ? WHERE bikes.id = ? AND bikes.deleted_at IS NOT NULL",
+            user_id,
+            bike_id
+        )
+        .fetch_optional(&self.0)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("No deleted bike found with id {bike_id}")))?;
+
+        match bike.role.as_deref().map(str::parse).transpose()? {
+            Some(BikeRole::Owner) => Ok(()),
+            _ => Err(AppError::Forbidden),
+        }
+    }
+
+    /// Rides can be restored by those who could delete them.
+    pub async fn assert_can_restore_ride(&self, ride: &RideModel, user_id: i64) -> AppResult<()> {
+        let bike = sqlx::query!(
+            "SELECT bikes.deleted_at, bike_members.role FROM bikes LEFT JOIN bike_members ON bike_members.bike_id = bikes.id AND bike_members.user_id = ? WHERE bikes.id = ?",
+            user_id,
+            ride.bike_id
+        )
+        .fetch_one(&self.0)
+        .await?;
+
+        let role: BikeRole = bike.role.as_deref().ok_or(AppError::Forbidden)?.parse()?;
+        role.assert_can_edit_ride(ride, user_id)?;
+        if bike.deleted_at.is_some() {
+            return Err(AppError::Conflict(String::from(
+                "The bike of the ride is deleted, restore the bike first",
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Restores the bike, optionally with the rides that were trashed together with it.
+    pub async fn restore_bike_in(
+        conn: &mut SqliteConnection,
+        bike_id: i64,
+        with_rides: bool,
+    ) -> AppResult<()> {
+        if with_rides {
+            sqlx::query!(
+                "UPDATE rides SET deleted_at = NULL WHERE bike_id = ?1 AND deleted_at >= (SELECT deleted_at FROM bikes WHERE id = ?1)",
+                bike_id
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        let affected = sqlx::query!(
+            "UPDATE bikes SET deleted_at = NULL WHERE id = ? AND deleted_at IS NOT NULL",
+            bike_id
+        )
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+
+        if affected == 0 {
+            return Err(AppError::NotFound(format!(
+                "No deleted bike found with id {bike_id}",
+            )));
+        }
+
+        Ok(())
+    }
+
+    pub async fn restore_ride_in(conn: &mut SqliteConnection, ride_id: i64) -> AppResult<()> {
+        let affected = sqlx::query!(
+            "UPDATE rides SET deleted_at = NULL WHERE id = ? AND deleted_at IS NOT NULL",
+            ride_id
+        )
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+
+        if affected == 0 {
+            return Err(AppError::NotFound(format!(
+                "No deleted ride found with id {ride_id}",
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Permanently deletes rides and bikes which were deleted before the cutoff, keeping their
+    /// history.
+    pub async fn purge(&self, deleted_before: &NaiveDateTime) -> AppResult<Purged> {
+        let cutoff = format_date_time(deleted_before);
+        let mut tx = self.0.begin().await?;
+        let rides = sqlx::query!(
+            "DELETE FROM rides WHERE deleted_at < ?1 OR bike_id IN (SELECT id FROM bikes WHERE deleted_at < ?1)",
+            cutoff
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        let photos = sqlx::query_scalar!(
+            r#"SELECT photo AS "photo!" FROM bikes WHERE deleted_at < ? AND photo IS NOT NULL"#,
+            cutoff
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let bikes = sqlx::query!("DELETE FROM bikes WHERE deleted_at < ?", cutoff)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        tx.commit().await?;
+        Ok(Purged {
+            rides,
+            bikes,
+            photos,
+        })
+    }
+}
@@ -0,0 +1,134 @@
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use axum::{Extension, Json, Router};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+
+use crate::services::auth::models::SessionModel;
+use crate::services::bikes::models::BikeModel;
+use crate::services::bikes::repository::BikeRepository;
+use crate::services::bikes::rides::models::RideWithBike;
+use crate::services::bikes::rides::repository::RideRepository;
+use crate::services::history::models::{Actor, HistoryAction, HistoryEntity};
+use crate::services::history::repository::HistoryRepository;
+use crate::utility::error::{AppError, AppResult};
+use crate::utility::state::AppState;
+
+use super::repository::TrashRepository;
+
+pub fn router_with_auth() -> Router<AppState> {
+    Router::new()
+        .route("/bikes", get(get_bikes))
+        .route("/bikes/{id}/restore", post(restore_bike))
+        .route("/rides", get(get_rides))
+        .route("/rides/{id}/restore", post(restore_ride))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RestoreBikeOptions {
+    /// Also restore the rides which were trashed together with the bike
+    #[serde(default)]
+    with_rides: bool,
+}
+
+async fn get_bikes(
+    State(trash_repo): State<TrashRepository>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<Json<Vec<BikeModel>>> {
+    let models = trash_repo.get_bikes(session.user_id).await?;
+    Ok(Json(models))
+}
+
+async fn restore_bike(
+    State(trash_repo): State<TrashRepository>,
+    State(pool): State<SqlitePool>,
+    Path(bike_id): Path<i64>,
+    Query(options): Query<RestoreBikeOptions>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<StatusCode> {
+    trash_repo
+        .assert_can_restore_bike(bike_id, session.user_id)
+        .await?;
+
+    let before = trash_repo.get_bike(bike_id).await?;
+    let rides = match options.with_rides {
+        true => trash_repo.get_rides_deleted_with_bike(bike_id).await?,
+        false => Vec::new(),
+    };
+    let mut tx = pool.begin().await?;
+    TrashRepository::restore_bike_in(&mut tx, bike_id, options.with_rides).await?;
+
+    let actor = Actor::User(session.user_id);
+    let after = BikeRepository::get_one_in(&mut tx, bike_id).await?;
+    HistoryRepository::record_in(
+        &mut tx,
+        HistoryEntity::Bike,
+        bike_id,
+        HistoryAction::Restore,
+        actor,
+        Some(&before),
+        Some(&after),
+    )
+    .await?;
+    for before in rides {
+        let after = RideRepository::get_one_in(&mut tx, before.id).await?;
+        HistoryRepository::record_in(
+            &mut tx,
+            HistoryEntity::Ride,
+            before.id,
+            HistoryAction::Restore,
+            actor,
+            Some(&before),
+            Some(&after),
+        )
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_rides(
+    State(trash_repo): State<TrashRepository>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<Json<Vec<RideWithBike>>> {
+    let models = trash_repo.get_rides(session.user_id).await?;
+    Ok(Json(models))
+}
+
+async fn restore_ride(
+    State(trash_repo): State<TrashRepository>,
+    State(ride_repo): State<RideRepository>,
+    State(pool): State<SqlitePool>,
+    Path(ride_id): Path<i64>,
+    Extension(session): Extension<SessionModel>,
+) -> AppResult<StatusCode> {
+    let before = ride_repo.get_one_including_deleted(ride_id).await?;
+    if before.deleted_at.is_none() {
+        return Err(AppError::NotFound(format!(
+            "No deleted ride found with id {ride_id}"
+        )));
+    }
+    trash_repo
+        .assert_can_restore_ride(&before, session.user_id)
+        .await?;
+
+    let mut tx = pool.begin().await?;
+    TrashRepository::restore_ride_in(&mut tx, ride_id).await?;
+    let after = RideRepository::get_one_in(&mut tx, ride_id).await?;
+    HistoryRepository::record_in(
+        &mut tx,
+        HistoryEntity::Ride,
+        ride_id,
+        HistoryAction::Restore,
+        Actor::User(session.user_id),
+        Some(&before),
+        Some(&after),
+    )
+    .await?;
+
+    tx.commit().await?;
+    Ok(StatusCode::NO_CONTENT)
+}
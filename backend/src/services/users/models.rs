@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 use crate::utility::{
     db_extensions::Model,
     db_format::{format_date_time, parse_date_time},
+    patch::present,
 };
 
 #[derive(Debug, Clone)]
@@ -63,6 +64,24 @@ pub struct UserPartial {
     pub monthly_goal: Option<f64>,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserPatch {
+    #[serde(default, deserialize_with = "present")]
+    pub username: Option<String>,
+    #[serde(default, deserialize_with = "present")]
+    pub monthly_goal: Option<Option<f64>>,
+}
+
+impl UserPatch {
+    pub fn apply(self, user: &UserModel) -> UserPartial {
+        UserPartial {
+            username: self.username.unwrap_or_else(|| user.username.clone()),
+            monthly_goal: self.monthly_goal.unwrap_or(user.monthly_goal),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct UserLogin {
     pub username: String,
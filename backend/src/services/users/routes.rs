@@ -1,13 +1,13 @@
 use axum::extract::State;
 use axum::http::StatusCode;
-use axum::routing::{delete, get, post, put};
+use axum::routing::{delete, get, patch, post, put};
 use axum::{Extension, Json, Router};
 
 use crate::services::auth::models::SessionModel;
 use crate::utility::error::{AppError, AppResult};
 use crate::utility::state::AppState;
 
-use super::models::{UserModel, UserPartial};
+use super::models::{UserModel, UserPartial, UserPatch};
 use super::repository::UserRepository;
 
 pub fn router() -> Router<AppState> {
@@ -18,6 +18,7 @@ pub fn router_with_auth() -> Router<AppState> {
     Router::new()
         .route("/", get(current_user))
         .route("/", put(update_user))
+        .route("/", patch(patch_user))
         .route("/", delete(delete_user))
 }
 
@@ -47,6 +48,23 @@ async fn update_user(
     Ok(Json(model))
 }
 
+async fn patch_user(
+    State(user_repo): State<UserRepository>,
+    Extension(user): Extension<UserModel>,
+    Json(patch): Json<UserPatch>,
+) -> AppResult<Json<UserModel>> {
+    if let Some(username) = &patch.username {
+        let existing = user_repo.try_get_by_username(username).await?;
+        if existing.is_some_and(|existing| existing.id != user.id) {
+            return Err(AppError::Conflict("Username already exists".to_string()));
+        }
+    }
+
+    let update = patch.apply(&user);
+    let model = user_repo.update(user.id, &update).await?;
+    Ok(Json(model))
+}
+
 async fn delete_user(
     State(repo): State<UserRepository>,
     Extension(session): Extension<SessionModel>,
@@ -1,4 +1,7 @@
 pub mod db_extensions;
 pub mod db_format;
 pub mod error;
+pub mod geometry;
+pub mod patch;
 pub mod state;
+pub mod versioning;
@@ -2,6 +2,8 @@ use chrono::{NaiveDate, NaiveDateTime};
 
 pub const DATE_FORMAT: &str = "%Y-%m-%d";
 pub const DATE_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+/// Format of timestamps with milliseconds written by SQLite triggers
+pub const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.3f";
 
 pub fn parse_date(s: &str) -> Result<NaiveDate, chrono::ParseError> {
     NaiveDate::parse_from_str(s, DATE_FORMAT)
@@ -18,3 +20,11 @@ pub fn format_date(d: &NaiveDate) -> String {
 pub fn format_date_time(dt: &NaiveDateTime) -> String {
     dt.format(DATE_TIME_FORMAT).to_string()
 }
+
+pub fn parse_timestamp(s: &str) -> Result<NaiveDateTime, chrono::ParseError> {
+    NaiveDateTime::parse_from_str(s, TIMESTAMP_FORMAT)
+}
+
+pub fn format_timestamp(dt: &NaiveDateTime) -> String {
+    dt.format(TIMESTAMP_FORMAT).to_string()
+}
@@ -1,11 +1,14 @@
 use std::string;
 
 use axum::extract::multipart::MultipartError;
-use axum::http::StatusCode;
+use axum::http::{header, StatusCode};
 use axum::response::{IntoResponse, Response};
+use axum::Json;
 use chrono::SecondsFormat;
 use csv::Writer;
 
+use super::versioning::etag;
+
 pub type AppResult<T> = Result<T, AppError>;
 
 pub enum AppError {
@@ -14,6 +17,9 @@ pub enum AppError {
     Unauthorized,
     Forbidden,
     Conflict(String),
+    /// Update was based on an outdated version, holds the current server copy
+    VersionConflict(i64, Box<serde_json::Value>),
+    PreconditionRequired(String),
     #[cfg_attr(not(debug_assertions), allow(dead_code))]
     Database(sqlx::Error),
     #[cfg_attr(not(debug_assertions), allow(dead_code))]
@@ -21,49 +27,63 @@ pub enum AppError {
 }
 
 impl AppError {
-    fn status_code(&self) -> StatusCode {
+    pub fn status_code(&self) -> StatusCode {
         match self {
             AppError::NotFound(_) => StatusCode::NOT_FOUND,
             AppError::BadRequest(_) => StatusCode::BAD_REQUEST,
             AppError::Unauthorized => StatusCode::UNAUTHORIZED,
             AppError::Forbidden => StatusCode::FORBIDDEN,
             AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::VersionConflict(..) => StatusCode::CONFLICT,
+            AppError::PreconditionRequired(_) => StatusCode::PRECONDITION_REQUIRED,
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 
     #[cfg(debug_assertions)]
-    fn message(&self) -> String {
+    pub fn message(&self) -> String {
         match self {
             AppError::NotFound(e) => format!("Not Found: {e}"),
             AppError::BadRequest(e) => format!("Bad Request: {e}"),
             AppError::Unauthorized => "Unauthorized".to_string(),
             AppError::Forbidden => "Forbidden".to_string(),
             AppError::Conflict(e) => format!("Conflict: {e}"),
+            AppError::VersionConflict(version, _) => {
+                format!("Conflict: Current version is {version}")
+            }
+            AppError::PreconditionRequired(e) => format!("Precondition Required: {e}"),
             AppError::Database(e) => format!("Database Error: {e}"),
             AppError::Other(e) => format!("Internal Server Error: {e}"),
         }
     }
 
     #[cfg(not(debug_assertions))]
-    fn message(&self) -> String {
+    pub fn message(&self) -> String {
         match self {
             AppError::NotFound(e) => format!("Not Found: {e}"),
             AppError::BadRequest(e) => format!("Bad Request: {e}"),
             AppError::Unauthorized => "Unauthorized".to_string(),
             AppError::Forbidden => "Forbidden".to_string(),
             AppError::Conflict(e) => format!("Conflict: {e}"),
+            AppError::VersionConflict(version, _) => {
+                format!("Conflict: Current version is {version}")
+            }
+            AppError::PreconditionRequired(e) => format!("Precondition Required: {e}"),
             _ => "Internal Server Error".to_string(),
         }
     }
 
-    fn detailed(&self) -> String {
+    pub fn detailed(&self) -> String {
         match self {
             AppError::NotFound(t) => format!("Not Found: {t}"),
             AppError::BadRequest(t) => format!("Bad Request: {t}"),
             AppError::Unauthorized => "Unauthorized".to_string(),
             AppError::Forbidden => "Forbidden".to_string(),
             AppError::Conflict(t) => format!("Conflict: {t}"),
+            AppError::VersionConflict(version, _) => {
+                format!("Version Conflict: Current version is {version}")
+            }
+            AppError::PreconditionRequired(t) => format!("Precondition Required: {t}"),
             AppError::Database(e) => format!("Database Error: {e}"),
             AppError::Other(e) => format!("Other Error: {e}"),
         }
@@ -78,6 +98,10 @@ impl IntoResponse for AppError {
             now.to_rfc3339_opts(SecondsFormat::Secs, false),
             self.detailed(),
         );
+        if let AppError::VersionConflict(version, current) = self {
+            let headers = [(header::ETAG, etag(version))];
+            return (StatusCode::CONFLICT, headers, Json(current)).into_response();
+        }
         (self.status_code(), self.message()).into_response()
     }
 }
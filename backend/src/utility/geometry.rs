@@ -0,0 +1,246 @@
+use serde::Serialize;
+
+/// Precision used by the encoded polyline format (5 decimal places).
+const POLYLINE_FACTOR: f64 = 1e5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub lat: f64,
+    pub lon: f64,
+}
+
+impl Point {
+    pub const fn new(lat: f64, lon: f64) -> Self {
+        Self { lat, lon }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        (-90.0..=90.0).contains(&self.lat) && (-180.0..=180.0).contains(&self.lon)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoundingBox {
+    pub min_lat: f64,
+    pub min_lon: f64,
+    pub max_lat: f64,
+    pub max_lon: f64,
+}
+
+impl BoundingBox {
+    pub fn from_points(points: &[Point]) -> Option<Self> {
+        let first = points.first()?;
+        let mut bbox = BoundingBox {
+            min_lat: first.lat,
+            min_lon: first.lon,
+            max_lat: first.lat,
+            max_lon: first.lon,
+        };
+
+        for point in &points[1..] {
+            bbox.min_lat = bbox.min_lat.min(point.lat);
+            bbox.min_lon = bbox.min_lon.min(point.lon);
+            bbox.max_lat = bbox.max_lat.max(point.lat);
+            bbox.max_lon = bbox.max_lon.max(point.lon);
+        }
+
+        Some(bbox)
+    }
+
+    /// Bounding box in the GeoJSON order `[west, south, east, north]`.
+    pub fn to_geojson(self) -> [f64; 4] {
+        [self.min_lon, self.min_lat, self.max_lon, self.max_lat]
+    }
+}
+
+/// Simplifies the line using the Douglas–Peucker algorithm.
+/// Tolerance is in degrees and the first and last points are always kept.
+pub fn simplify(points: &[Point], tolerance: f64) -> Vec<Point> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+
+    let mut stack = vec![(0, points.len() - 1)];
+    while let Some((start, end)) = stack.pop() {
+        let mut max_distance = 0.0;
+        let mut max_index = start;
+        for i in (start + 1)..end {
+            let distance = perpendicular_distance(&points[i], &points[start], &points[end]);
+            if distance > max_distance {
+                max_distance = distance;
+                max_index = i;
+            }
+        }
+
+        if max_distance > tolerance {
+            keep[max_index] = true;
+            stack.push((start, max_index));
+            stack.push((max_index, end));
+        }
+    }
+
+    points
+        .iter()
+        .zip(keep)
+        .filter_map(|(point, keep)| keep.then_some(*point))
+        .collect()
+}
+
+fn perpendicular_distance(point: &Point, start: &Point, end: &Point) -> f64 {
+    let dx = end.lon - start.lon;
+    let dy = end.lat - start.lat;
+    let length = dx.hypot(dy);
+    if length == 0.0 {
+        return (point.lon - start.lon).hypot(point.lat - start.lat);
+    }
+
+    (dy * point.lon - dx * point.lat + end.lon * start.lat - end.lat * start.lon).abs() / length
+}
+
+/// Encodes the points using the Google encoded polyline algorithm.
+pub fn encode_polyline(points: &[Point]) -> String {
+    let mut result = String::new();
+    let mut previous = (0, 0);
+    for point in points {
+        let lat = (point.lat * POLYLINE_FACTOR).round() as i64;
+        let lon = (point.lon * POLYLINE_FACTOR).round() as i64;
+        encode_value(lat - previous.0, &mut result);
+        encode_value(lon - previous.1, &mut result);
+        previous = (lat, lon);
+    }
+
+    result
+}
+
+fn encode_value(value: i64, output: &mut String) {
+    let mut value = if value < 0 { !(value << 1) } else { value << 1 };
+    while value >= 0x20 {
+        output.push(char::from((((value & 0x1f) | 0x20) + 63) as u8));
+        value >>= 5;
+    }
+    output.push(char::from((value + 63) as u8));
+}
+
+/// Decodes a Google encoded polyline, as used by Strava summary maps.
+pub fn decode_polyline(polyline: &str) -> anyhow::Result<Vec<Point>> {
+    let mut points = Vec::new();
+    let mut bytes = polyline.bytes();
+    let mut lat = 0;
+    let mut lon = 0;
+    while let Some(delta_lat) = decode_value(&mut bytes)? {
+        let delta_lon = decode_value(&mut bytes)?
+            .ok_or_else(|| anyhow::anyhow!("Polyline ends in the middle of a point"))?;
+        lat += delta_lat;
+        lon += delta_lon;
+        points.push(Point::new(
+            lat as f64 / POLYLINE_FACTOR,
+            lon as f64 / POLYLINE_FACTOR,
+        ));
+    }
+
+    Ok(points)
+}
+
+fn decode_value(bytes: &mut impl Iterator<Item = u8>) -> anyhow::Result<Option<i64>> {
+    let mut result = 0i64;
+    let mut shift = 0;
+    loop {
+        let byte = match bytes.next() {
+            Some(byte) => byte,
+            None if shift == 0 => return Ok(None),
+            None => anyhow::bail!("Polyline ends in the middle of a value"),
+        };
+        if !(63..127).contains(&byte) || shift > 60 {
+            anyhow::bail!("Invalid polyline character '{}'", char::from(byte));
+        }
+
+        let chunk = i64::from(byte - 63);
+        result |= (chunk & 0x1f) << shift;
+        shift += 5;
+        if chunk < 0x20 {
+            break;
+        }
+    }
+
+    let value = if result & 1 == 1 {
+        !(result >> 1)
+    } else {
+        result >> 1
+    };
+    Ok(Some(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Example of the polyline algorithm documentation.
+    const POLYLINE: &str = "_p~iF~ps|U_ulLnnqC_mqNvxq`@";
+
+    fn example_points() -> Vec<Point> {
+        vec![
+            Point::new(38.5, -120.2),
+            Point::new(40.7, -120.95),
+            Point::new(43.252, -126.453),
+        ]
+    }
+
+    #[test]
+    fn encodes_polyline() {
+        assert_eq!(encode_polyline(&example_points()), POLYLINE);
+        assert_eq!(encode_polyline(&[]), "");
+    }
+
+    #[test]
+    fn decodes_encoded_polyline() {
+        let points = decode_polyline(POLYLINE).unwrap();
+        assert_eq!(points, example_points());
+        assert_eq!(decode_polyline(&encode_polyline(&points)).unwrap(), points);
+        assert!(decode_polyline("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_truncated_polyline() {
+        // Latitude of the third point without its longitude
+        assert!(decode_polyline("_p~iF~ps|U_ulLnnqC_mqN").is_err());
+        // Value cut off after a continuation chunk
+        assert!(decode_polyline("_p~iF~ps|U_").is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_polyline_characters() {
+        assert!(decode_polyline("_p~iF ps|U").is_err());
+        assert!(decode_polyline("_p~iF~ps|Ü").is_err());
+        // Too many continuation chunks overflow the value
+        assert!(decode_polyline(&"~".repeat(20)).is_err());
+    }
+
+    #[test]
+    fn keeps_short_lines() {
+        assert!(simplify(&[], 1.0).is_empty());
+
+        let point = [Point::new(1.0, 2.0)];
+        assert_eq!(simplify(&point, 1.0), point);
+
+        let line = [Point::new(1.0, 2.0), Point::new(3.0, 4.0)];
+        assert_eq!(simplify(&line, 1.0), line);
+    }
+
+    #[test]
+    fn simplifies_points_within_tolerance() {
+        let points = [
+            Point::new(0.0, 0.0),
+            Point::new(0.001, 1.0),
+            Point::new(0.0, 2.0),
+            Point::new(1.0, 3.0),
+            Point::new(0.0, 4.0),
+        ];
+        let simplified = simplify(&points, 0.01);
+        assert_eq!(simplified, [points[0], points[2], points[3], points[4]]);
+    }
+}
@@ -0,0 +1,13 @@
+use serde::{Deserialize, Deserializer};
+
+/// Deserializes a field of a JSON Merge Patch (RFC 7396).
+///
+/// Combined with `#[serde(default)]` an absent field stays `None`, while for
+/// `Option<Option<T>>` an explicit `null` becomes `Some(None)` and clears the value.
+pub fn present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
@@ -5,8 +5,19 @@ use crate::{
     config::Configuration,
     services::{
         auth::repository::AuthRepository,
-        bikes::{repository::BikeRepository, rides::repository::RideRepository},
+        bikes::{
+            maintenance::repository::MaintenanceRepository,
+            members::repository::MemberRepository,
+            photos::storage::PhotoStorage,
+            repository::BikeRepository,
+            rides::{repository::RideRepository, tracks::repository::TrackRepository},
+        },
+        components::repository::ComponentRepository,
+        history::repository::HistoryRepository,
+        reminders::repository::ReminderRepository,
         strava::{api::no_auth::StravaApiNoAuth, repository::StravaRepository},
+        transfers::repository::TransferRepository,
+        trash::repository::TrashRepository,
         users::repository::UserRepository,
     },
 };
@@ -16,30 +27,57 @@ pub struct AppState {
     config: Configuration,
     pool: SqlitePool,
     rides: RideRepository,
+    tracks: TrackRepository,
     bikes: BikeRepository,
     users: UserRepository,
     auth: AuthRepository,
     strava: StravaRepository,
     strava_api: Option<StravaApiNoAuth>,
+    trash: TrashRepository,
+    history: HistoryRepository,
+    components: ComponentRepository,
+    maintenance: MaintenanceRepository,
+    reminders: ReminderRepository,
+    photos: PhotoStorage,
+    members: MemberRepository,
+    transfers: TransferRepository,
 }
 
 impl AppState {
     pub fn new(config: Configuration, pool: SqlitePool) -> Self {
         let rides = RideRepository::new(pool.clone());
+        let tracks = TrackRepository::new(pool.clone());
         let bikes = BikeRepository::new(pool.clone());
         let users = UserRepository::new(pool.clone());
         let auth = AuthRepository::new(pool.clone());
         let strava = StravaRepository::new(pool.clone());
         let strava_api = config.strava_config().map(StravaApiNoAuth::new);
+        let trash = TrashRepository::new(pool.clone());
+        let history = HistoryRepository::new(pool.clone());
+        let components = ComponentRepository::new(pool.clone());
+        let maintenance = MaintenanceRepository::new(pool.clone());
+        let reminders = ReminderRepository::new(pool.clone());
+        let photos = PhotoStorage::new(&config.media_dir);
+        let members = MemberRepository::new(pool.clone());
+        let transfers = TransferRepository::new(pool.clone());
         Self {
             config,
             pool,
             rides,
+            tracks,
             bikes,
             users,
             auth,
             strava,
             strava_api,
+            trash,
+            history,
+            components,
+            maintenance,
+            reminders,
+            photos,
+            members,
+            transfers,
         }
     }
 }
@@ -62,6 +100,12 @@ impl FromRef<AppState> for RideRepository {
     }
 }
 
+impl FromRef<AppState> for TrackRepository {
+    fn from_ref(state: &AppState) -> Self {
+        state.tracks.clone()
+    }
+}
+
 impl FromRef<AppState> for BikeRepository {
     fn from_ref(state: &AppState) -> Self {
         state.bikes.clone()
@@ -91,3 +135,51 @@ impl FromRef<AppState> for Option<StravaApiNoAuth> {
         state.strava_api.clone()
     }
 }
+
+impl FromRef<AppState> for TrashRepository {
+    fn from_ref(state: &AppState) -> Self {
+        state.trash.clone()
+    }
+}
+
+impl FromRef<AppState> for HistoryRepository {
+    fn from_ref(state: &AppState) -> Self {
+        state.history.clone()
+    }
+}
+
+impl FromRef<AppState> for ComponentRepository {
+    fn from_ref(state: &AppState) -> Self {
+        state.components.clone()
+    }
+}
+
+impl FromRef<AppState> for MaintenanceRepository {
+    fn from_ref(state: &AppState) -> Self {
+        state.maintenance.clone()
+    }
+}
+
+impl FromRef<AppState> for ReminderRepository {
+    fn from_ref(state: &AppState) -> Self {
+        state.reminders.clone()
+    }
+}
+
+impl FromRef<AppState> for PhotoStorage {
+    fn from_ref(state: &AppState) -> Self {
+        state.photos.clone()
+    }
+}
+
+impl FromRef<AppState> for MemberRepository {
+    fn from_ref(state: &AppState) -> Self {
+        state.members.clone()
+    }
+}
+
+impl FromRef<AppState> for TransferRepository {
+    fn from_ref(state: &AppState) -> Self {
+        state.transfers.clone()
+    }
+}
@@ -0,0 +1,67 @@
+use axum::http::{header, HeaderMap, HeaderValue};
+use serde::Serialize;
+
+use super::error::{AppError, AppResult};
+
+pub fn etag(version: i64) -> HeaderValue {
+    HeaderValue::from_str(&format!("\"{version}\"")).expect("version is a valid header value")
+}
+
+pub fn etag_headers(version: i64) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(header::ETAG, etag(version));
+    headers
+}
+
+/// Version required by the `If-Match` header, `None` when any version matches (`*`).
+pub fn if_match(headers: &HeaderMap) -> AppResult<Option<i64>> {
+    let value = headers
+        .get(header::IF_MATCH)
+        .ok_or_else(|| AppError::PreconditionRequired(String::from("Missing If-Match header")))?
+        .to_str()
+        .map_err(|_| AppError::BadRequest(String::from("Invalid If-Match header")))?
+        .trim();
+
+    if value == "*" {
+        return Ok(None);
+    }
+
+    value
+        .trim_start_matches("W/")
+        .trim_matches('"')
+        .parse()
+        .map(Some)
+        .map_err(|_| AppError::BadRequest(format!("Invalid If-Match header {value}")))
+}
+
+/// Whether the `If-None-Match` header matches the entity tag of the current representation.
+pub fn if_none_match(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| {
+            value
+                .split(',')
+                .map(|tag| tag.trim().trim_start_matches("W/"))
+                .any(|tag| tag == etag || tag == "*")
+        })
+}
+
+/// Fails with the current server copy when the client edited an outdated version.
+pub fn assert_version<T: Serialize>(
+    expected: Option<i64>,
+    version: i64,
+    current: &T,
+) -> AppResult<()> {
+    match expected {
+        Some(expected) if expected != version => Err(conflict(version, current)),
+        _ => Ok(()),
+    }
+}
+
+pub fn conflict<T: Serialize>(version: i64, current: &T) -> AppError {
+    match serde_json::to_value(current) {
+        Ok(current) => AppError::VersionConflict(version, Box::new(current)),
+        Err(e) => AppError::Other(e.into()),
+    }
+}
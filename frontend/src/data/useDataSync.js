@@ -2,9 +2,11 @@ import { useEffect } from "react";
 import { notifications } from "@mantine/notifications";
 import { isWorkerAvailable, workerStream } from "../lib/WorkerCom.js";
 import { useApiClient } from "../components/AuthContext.jsx";
+import { useQueryClient } from "@tanstack/react-query";
 
 export function useDataSync() {
     const apiClient = useApiClient();
+    const queryClient = useQueryClient();
 
     function handleStreamMessage({ type, category, itemCount }) {
         if (type === "started") {
@@ -26,6 +28,17 @@ export function useDataSync() {
             });
         }
 
+        if (type === "conflicted") {
+            notifications.show({
+                title: "Sync conflict",
+                message: `Discarded ${itemCount} offline edits of ${category}, they were changed on the server meanwhile`,
+                autoClose: 10000,
+                withBorder: true,
+                color: "orange",
+            });
+            queryClient.invalidateQueries({ queryKey: [category] });
+        }
+
         if (type === "completed") {
             notifications.show({
                 title: "Sync completed",
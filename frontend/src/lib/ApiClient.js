@@ -17,6 +17,14 @@ export class ApiError extends Error {
     }
 }
 
+/**
+ * @param {number} version
+ * @returns {{"If-Match": string}}
+ */
+export function ifMatch(version) {
+    return { "If-Match": `"${version}"` };
+}
+
 export default class ApiClient {
     /** @type {string | null} */
     authToken = null;
@@ -112,13 +120,20 @@ export default class ApiClient {
         );
     }
 
-    async put(resource, body, expectFail = false, timeout = DEFAULT_TIMEOUT) {
+    async put(
+        resource,
+        body,
+        expectFail = false,
+        timeout = DEFAULT_TIMEOUT,
+        headers = {}
+    ) {
         return this.#request(
             resource,
             {
                 method: "PUT",
                 headers: {
                     "Content-Type": "application/json",
+                    ...headers,
                 },
                 body: JSON.stringify(body),
             },
@@ -127,10 +142,15 @@ export default class ApiClient {
         );
     }
 
-    async delete(resource, expectFail = false, timeout = DEFAULT_TIMEOUT) {
+    async delete(
+        resource,
+        expectFail = false,
+        timeout = DEFAULT_TIMEOUT,
+        headers = {}
+    ) {
         return this.#request(
             resource,
-            { method: "DELETE" },
+            { method: "DELETE", headers },
             expectFail,
             timeout
         );
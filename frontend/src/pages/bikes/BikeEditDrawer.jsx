@@ -16,6 +16,18 @@ export default function BikeEditDrawer({
     description,
     color,
     stravaGear,
+    initialDistance,
+    initialDistanceDate,
+    bikeType,
+    frameSize,
+    wheelSize,
+    weight,
+    modelYear,
+    purchaseDate,
+    purchasePrice,
+    currency,
+    retiredAt,
+    version,
     onClose,
 }) {
     const bikeService = useBikeService();
@@ -24,14 +36,23 @@ export default function BikeEditDrawer({
 
     const queryClient = useQueryClient();
     const updateMutation = useMutation({
-        mutationFn: (values) => bikeService.update(id, bikeFormToBody(values)),
+        mutationFn: (values) =>
+            bikeService.update(id, bikeFormToBody(values), version),
+        onSuccess: () => {
+            queryClient.invalidateQueries({ queryKey: ["bikes"] });
+            onClose();
+        },
+    });
+    const retireMutation = useMutation({
+        mutationFn: () =>
+            retiredAt ? bikeService.unretire(id) : bikeService.retire(id),
         onSuccess: () => {
             queryClient.invalidateQueries({ queryKey: ["bikes"] });
             onClose();
         },
     });
     const deleteMutation = useMutation({
-        mutationFn: () => bikeService.delete(id),
+        mutationFn: () => bikeService.delete(id, version),
         onSuccess: () => {
             queryClient.invalidateQueries({ queryKey: ["bikes"] });
             onClose();
@@ -61,8 +82,35 @@ export default function BikeEditDrawer({
             hasColor: Boolean(color),
             color: color || "",
             stravaGear: stravaGear,
+            initialDistance,
+            initialDistanceDate: initialDistanceDate
+                ? new Date(initialDistanceDate)
+                : null,
+            bikeType,
+            frameSize,
+            wheelSize,
+            weight,
+            modelYear,
+            purchaseDate: purchaseDate ? new Date(purchaseDate) : null,
+            purchasePrice,
+            currency,
         });
-    }, [name, description, color, stravaGear]);
+    }, [
+        name,
+        description,
+        color,
+        stravaGear,
+        initialDistance,
+        initialDistanceDate,
+        bikeType,
+        frameSize,
+        wheelSize,
+        weight,
+        modelYear,
+        purchaseDate,
+        purchasePrice,
+        currency,
+    ]);
 
     return (
         <Drawer
@@ -91,6 +139,15 @@ export default function BikeEditDrawer({
                         >
                             Delete
                         </Button>
+                        <Button
+                            variant="light"
+                            color="gray"
+                            onClick={retireMutation.mutate}
+                            loading={retireMutation.isPending}
+                            disabled={updateMutation.isPending || !online}
+                        >
+                            {retiredAt ? "Unretire" : "Retire"}
+                        </Button>
                         <Button
                             variant="filled"
                             type="submit"
@@ -1,6 +1,7 @@
 import React from "react";
 import {
     ActionIcon,
+    Badge,
     ColorSwatch,
     Group,
     Paper,
@@ -17,10 +18,9 @@ export default function BikeEntry({
     color,
     onEditBike,
     stravaGear,
+    retiredAt,
+    role,
 }) {
-    function handleOnEditClick() {
-        onEditBike({ id, name, description, color, stravaGear });
-    }
 
     return (
         <Paper withBorder p="md">
@@ -29,16 +29,19 @@ export default function BikeEntry({
                     <Text fw="bold" size="lg" truncate="end" flex="1 1 auto">
                         {name}
                     </Text>
+                    {retiredAt && <Badge color="gray">Retired</Badge>}
                     {stravaGear && <IconBrandStrava size={24} />}
                     <ColorSwatch color={color} />
-                    <WhenOnline>
-                        <ActionIcon
-                            variant="filled"
-                            onClick={handleOnEditClick}
-                        >
-                            <IconPencil />
-                        </ActionIcon>
-                    </WhenOnline>
+                    {role !== "owner" && (
+                        <Badge variant="light">{role}</Badge>
+                    )}
+                    {role === "owner" && (
+                        <WhenOnline>
+                            <ActionIcon variant="filled" onClick={onEditBike}>
+                                <IconPencil />
+                            </ActionIcon>
+                        </WhenOnline>
+                    )}
                 </Group>
                 {description && (
                     <Text lineClamp={3} style={{ overflowWrap: "anywhere" }}>
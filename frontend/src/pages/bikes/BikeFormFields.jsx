@@ -9,7 +9,12 @@ import {
     InputBase,
     useCombobox,
     Input,
+    NumberInput,
+    Select,
+    Fieldset,
+    Group,
 } from "@mantine/core";
+import { DateInput } from "@mantine/dates";
 import useStravaService from "../../services/stravaService.js";
 import { useQuery } from "@tanstack/react-query";
 
@@ -53,6 +58,87 @@ export default function BikeFormFields({ form, disabled }) {
                 {...form.getInputProps("description")}
                 disabled={disabled}
             />
+            <NumberInput
+                label="Initial distance"
+                description="Distance ridden before tracking the bike"
+                placeholder="(km)"
+                min={0}
+                key={form.key("initialDistance")}
+                {...form.getInputProps("initialDistance")}
+                disabled={disabled}
+            />
+            <DateInput
+                label="Initial distance date"
+                placeholder="(optional)"
+                clearable
+                key={form.key("initialDistanceDate")}
+                {...form.getInputProps("initialDistanceDate")}
+                disabled={disabled}
+            />
+            <Fieldset legend="Specifications" disabled={disabled}>
+                <Select
+                    label="Type"
+                    placeholder="(optional)"
+                    clearable
+                    data={bikeTypes}
+                    key={form.key("bikeType")}
+                    {...form.getInputProps("bikeType")}
+                />
+                <Group grow>
+                    <TextInput
+                        label="Frame size"
+                        placeholder="(optional)"
+                        key={form.key("frameSize")}
+                        {...form.getInputProps("frameSize")}
+                    />
+                    <TextInput
+                        label="Wheel size"
+                        placeholder="(optional)"
+                        key={form.key("wheelSize")}
+                        {...form.getInputProps("wheelSize")}
+                    />
+                </Group>
+                <Group grow>
+                    <NumberInput
+                        label="Weight"
+                        placeholder="(kg)"
+                        min={0}
+                        key={form.key("weight")}
+                        {...form.getInputProps("weight")}
+                    />
+                    <NumberInput
+                        label="Model year"
+                        placeholder="(optional)"
+                        allowDecimal={false}
+                        key={form.key("modelYear")}
+                        {...form.getInputProps("modelYear")}
+                    />
+                </Group>
+            </Fieldset>
+            <Fieldset legend="Purchase" disabled={disabled}>
+                <DateInput
+                    label="Purchase date"
+                    placeholder="(optional)"
+                    clearable
+                    key={form.key("purchaseDate")}
+                    {...form.getInputProps("purchaseDate")}
+                />
+                <Group grow>
+                    <NumberInput
+                        label="Price"
+                        placeholder="(optional)"
+                        min={0}
+                        key={form.key("purchasePrice")}
+                        {...form.getInputProps("purchasePrice")}
+                    />
+                    <TextInput
+                        label="Currency"
+                        placeholder="EUR"
+                        key={form.key("currency")}
+                        {...form.getInputProps("currency")}
+                    />
+                </Group>
+            </Fieldset>
             <Checkbox
                 label="Custom Color"
                 key={form.key("hasColor")}
@@ -113,6 +199,16 @@ export default function BikeFormFields({ form, disabled }) {
     );
 }
 
+const bikeTypes = [
+    { value: "road", label: "Road" },
+    { value: "gravel", label: "Gravel" },
+    { value: "mountain", label: "Mountain" },
+    { value: "city", label: "City" },
+    { value: "touring", label: "Touring" },
+    { value: "electric", label: "Electric" },
+    { value: "other", label: "Other" },
+];
+
 function stravaBikeName(bikes, bikeId) {
     if (!bikeId) {
         return <Input.Placeholder>None</Input.Placeholder>;
@@ -11,8 +11,8 @@ export default function BikesPage() {
     const [editedBike, setEditedBike] = useState(null);
 
     const bikesQuery = useQuery({
-        queryKey: ["bikes"],
-        queryFn: bikeService.getAll,
+        queryKey: ["bikes", "withRetired"],
+        queryFn: () => bikeService.getAll(true),
     });
 
     return (
@@ -31,12 +31,19 @@ export default function BikesPage() {
                                 </Text>
                             </Paper>
                         )}
-                        {(bikesQuery.data ?? []).map((bike) => (
-                            <BikeEntry
-                                {...bike}
-                                key={bike.id}
-                                onEditBike={setEditedBike}
-                            />
+                        {(bikesQuery.data ?? []).map((bike, i, bikes) => (
+                            <React.Fragment key={bike.id}>
+                                {bike.group &&
+                                    bike.group !== bikes[i - 1]?.group && (
+                                        <Text fw="bold" c="dimmed">
+                                            {bike.group}
+                                        </Text>
+                                    )}
+                                <BikeEntry
+                                    {...bike}
+                                    onEditBike={() => setEditedBike(bike)}
+                                />
+                            </React.Fragment>
                         ))}
                     </Stack>
                 </Skeleton>
@@ -47,6 +54,18 @@ export default function BikesPage() {
                 description={editedBike?.description ?? ""}
                 color={editedBike?.color ?? ""}
                 stravaGear={editedBike?.stravaGear ?? null}
+                initialDistance={editedBike?.initialDistance ?? 0}
+                initialDistanceDate={editedBike?.initialDistanceDate ?? null}
+                bikeType={editedBike?.bikeType ?? null}
+                frameSize={editedBike?.frameSize ?? ""}
+                wheelSize={editedBike?.wheelSize ?? ""}
+                weight={editedBike?.weight ?? ""}
+                modelYear={editedBike?.modelYear ?? ""}
+                purchaseDate={editedBike?.purchaseDate ?? null}
+                purchasePrice={editedBike?.purchasePrice ?? ""}
+                currency={editedBike?.currency ?? ""}
+                retiredAt={editedBike?.retiredAt ?? null}
+                version={editedBike?.version}
                 onClose={() => setEditedBike(null)}
             />
         </Container>
@@ -14,6 +14,16 @@ export const bikeForm = {
         hasColor: false,
         color: "",
         stravaGear: null,
+        initialDistance: 0,
+        initialDistanceDate: null,
+        bikeType: null,
+        frameSize: "",
+        wheelSize: "",
+        weight: "",
+        modelYear: "",
+        purchaseDate: null,
+        purchasePrice: "",
+        currency: "",
     },
     validate: {
         name: nameValidator,
@@ -26,5 +36,23 @@ export function bikeFormToBody(values) {
         description: values.description || null,
         color: values.hasColor ? values.color : null,
         stravaGear: values.stravaGear || null,
+        initialDistance: values.initialDistance || 0,
+        initialDistanceDate: values.initialDistanceDate
+            ? values.initialDistanceDate.toISOString().split("T")[0]
+            : null,
+        bikeType: values.bikeType || null,
+        frameSize: values.frameSize || null,
+        wheelSize: values.wheelSize || null,
+        weight: numberOrNull(values.weight),
+        modelYear: numberOrNull(values.modelYear),
+        purchaseDate: values.purchaseDate
+            ? values.purchaseDate.toISOString().split("T")[0]
+            : null,
+        purchasePrice: numberOrNull(values.purchasePrice),
+        currency: values.currency || null,
     };
 }
+
+function numberOrNull(value) {
+    return typeof value === "number" ? value : null;
+}
@@ -15,6 +15,8 @@ export default function RideEditDrawer({
     distance,
     description,
     stravaRide,
+    movingTime,
+    version,
     onClose,
 }) {
     const [selectedBike, _] = useRecoilState(selectedBikeIdAtom);
@@ -23,7 +25,8 @@ export default function RideEditDrawer({
 
     const queryClient = useQueryClient();
     const updateMutation = useMutation({
-        mutationFn: (values) => rideService.update(id, rideFormToBody(values)),
+        mutationFn: (values) =>
+            rideService.update(id, rideFormToBody(values), version),
         onSuccess: (data) => {
             const newDate = new Date(data.date);
             queryClient.invalidateQueries({
@@ -68,7 +71,7 @@ export default function RideEditDrawer({
         },
     });
     const deleteMutation = useMutation({
-        mutationFn: () => rideService.delete(id),
+        mutationFn: () => rideService.delete(id, version),
         onSuccess: () => {
             const oldDate = new Date(date);
             queryClient.invalidateQueries({
@@ -111,8 +114,9 @@ export default function RideEditDrawer({
             distance,
             description,
             stravaRide,
+            movingTime,
         });
-    }, [date, distance, description, stravaRide]);
+    }, [date, distance, description, stravaRide, movingTime]);
 
     return (
         <Drawer
@@ -83,6 +83,8 @@ export default function RidesPage() {
                     distance={editedRide?.distance}
                     description={editedRide?.description}
                     stravaRide={editedRide?.stravaRide}
+                    movingTime={editedRide?.movingTime}
+                    version={editedRide?.version}
                     onClose={() => setEditedRide(null)}
                 />
             </WithSelectedBike>
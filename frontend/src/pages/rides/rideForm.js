@@ -13,6 +13,7 @@ export const rideForm = {
         distance: "",
         description: "",
         stravaRide: null,
+        movingTime: null,
     },
     validate: {
         distance: distanceValidator,
@@ -25,5 +26,6 @@ export function rideFormToBody(values) {
         distance: values.distance,
         description: values.description || null,
         stravaRide: values.stravaRide || null,
+        movingTime: values.movingTime || null,
     };
 }
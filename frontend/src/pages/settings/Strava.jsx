@@ -1,5 +1,5 @@
 import React, { useEffect, useState } from "react";
-import { Stack, Button, Text } from "@mantine/core";
+import { Stack, Button, Text, Checkbox } from "@mantine/core";
 import { useRecoilState } from "recoil";
 import { networkStatusAtom } from "../../data/useNetworkStatus.jsx";
 import useStravaService from "../../services/stravaService.js";
@@ -11,6 +11,7 @@ import { DateTimePicker } from "@mantine/dates";
 export default function Strava() {
     const [isOnline, _] = useRecoilState(networkStatusAtom);
     const [loadingRedirect, setLoadingRedirect] = useState(false);
+    const [importBikes, setImportBikes] = useState(false);
     const queryClient = useQueryClient();
     const stravaService = useStravaService();
     const statusService = useStatusService();
@@ -49,7 +50,7 @@ export default function Strava() {
 
     function createLink() {
         setLoadingRedirect(true);
-        stravaService.getOAuthRedirect().then((response) => {
+        stravaService.getOAuthRedirect(importBikes).then((response) => {
             window.location.href = response.url;
         });
     }
@@ -103,6 +104,13 @@ export default function Strava() {
 
     return (
         <Stack>
+            <Checkbox
+                label="Import bikes from Strava"
+                checked={importBikes}
+                onChange={(event) =>
+                    setImportBikes(event.currentTarget.checked)
+                }
+            />
             <Button
                 color="orange"
                 loading={loadingRedirect}
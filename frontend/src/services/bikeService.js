@@ -1,4 +1,5 @@
 import { useApiClient } from "../components/AuthContext.jsx";
+import { ifMatch } from "../lib/ApiClient.js";
 
 /**
  * @typedef BikeModel
@@ -10,6 +11,24 @@ import { useApiClient } from "../components/AuthContext.jsx";
  * @property {?string} color
  * @property {?string} deletedAt
  * @property {?string} stravaGear
+ * @property {number} initialDistance
+ * @property {?string} initialDistanceDate
+ * @property {?string} bikeType
+ * @property {?string} frameSize
+ * @property {?string} wheelSize
+ * @property {?number} weight
+ * @property {?number} modelYear
+ * @property {?string} purchaseDate
+ * @property {?number} purchasePrice
+ * @property {?string} currency
+ * @property {?string} photo
+ * @property {?string} retiredAt
+ * @property {{initialDistance: number, ridesDistance: number, distance: number}} odometer
+ * @property {"owner" | "rider" | "viewer"} role
+ * @property {number} sortOrder
+ * @property {?string} group
+ * @property {string} updatedAt
+ * @property {number} version
  */
 
 /**
@@ -19,16 +38,61 @@ import { useApiClient } from "../components/AuthContext.jsx";
  * @property {?string} description
  * @property {?string} color
  * @property {?string} stravaGear
+ * @property {number} initialDistance
+ * @property {?string} initialDistanceDate
+ * @property {?string} bikeType
+ * @property {?string} frameSize
+ * @property {?string} wheelSize
+ * @property {?number} weight
+ * @property {?number} modelYear
+ * @property {?string} purchaseDate
+ * @property {?number} purchasePrice
+ * @property {?string} currency
+ */
+
+/**
+ * @typedef BikeCosts
+ * @type {object}
+ * @property {number} bikeId
+ * @property {?string} currency
+ * @property {number} purchasePrice
+ * @property {number} maintenanceCost
+ * @property {number} totalCost
+ * @property {number} distance
+ * @property {?number} costPerKm
+ */
+
+/**
+ * @typedef BikeMember
+ * @type {object}
+ * @property {number} bikeId
+ * @property {number} userId
+ * @property {string} username
+ * @property {"owner" | "rider" | "viewer"} role
+ * @property {string} createdAt
+ */
+
+/**
+ * @typedef RiderTotals
+ * @type {object}
+ * @property {?number} riderId
+ * @property {?string} username
+ * @property {number} rides
+ * @property {number} distance
+ * @property {number} movingTime
  */
 
 export default function useBikeService() {
     const client = useApiClient();
 
     /**
+     * @param {boolean} withRetired
      * @returns {Promise<BikeModel[]>}
      */
-    function getAll() {
-        return client.get("/api/bikes");
+    function getAll(withRetired = false) {
+        return client.get(
+            withRetired ? "/api/bikes?retired=true" : "/api/bikes"
+        );
     }
 
     /**
@@ -50,19 +114,133 @@ export default function useBikeService() {
     /**
      * @param {number} bikeId
      * @param {BikePartial} data
+     * @param {number} version
+     * @returns {Promise<BikeModel>}
+     */
+    function update(bikeId, data, version) {
+        return client.put(
+            `/api/bikes/${bikeId}`,
+            data,
+            false,
+            undefined,
+            ifMatch(version)
+        );
+    }
+
+    /**
+     * @param {number} bikeId
+     * @param {number} version
+     * @returns {Promise<void>}
+     */
+    function deleteBike(bikeId, version) {
+        return client.delete(
+            `/api/bikes/${bikeId}`,
+            false,
+            undefined,
+            ifMatch(version)
+        );
+    }
+
+    /**
+     * @param {number} bikeId
      * @returns {Promise<BikeModel>}
      */
-    function update(bikeId, data) {
-        return client.put(`/api/bikes/${bikeId}`, data);
+    function retire(bikeId) {
+        return client.post(`/api/bikes/${bikeId}/retire`);
+    }
+
+    /**
+     * @param {number} bikeId
+     * @returns {Promise<BikeModel>}
+     */
+    function unretire(bikeId) {
+        return client.post(`/api/bikes/${bikeId}/unretire`);
+    }
+
+    /**
+     * @returns {Promise<BikeCosts[]>}
+     */
+    function getAllCosts() {
+        return client.get("/api/bikes/costs");
+    }
+
+    /**
+     * Arranges the bikes in the given order, bikes left out stay below them.
+     * @param {{bikeId: number, group: ?string}[]} order
+     * @returns {Promise<void>}
+     */
+    function reorder(order) {
+        return client.put("/api/bikes/order", order);
+    }
+
+    /**
+     * @param {number} bikeId
+     * @returns {Promise<BikeCosts>}
+     */
+    function getCosts(bikeId) {
+        return client.get(`/api/bikes/${bikeId}/costs`);
+    }
+
+    /**
+     * @param {number} bikeId
+     * @returns {Promise<BikeMember[]>}
+     */
+    function getMembers(bikeId) {
+        return client.get(`/api/bikes/${bikeId}/members`);
+    }
+
+    /**
+     * @param {number} bikeId
+     * @param {string} username
+     * @param {"owner" | "rider" | "viewer"} role
+     * @returns {Promise<BikeMember>}
+     */
+    function addMember(bikeId, username, role) {
+        return client.post(`/api/bikes/${bikeId}/members`, { username, role });
+    }
+
+    /**
+     * @param {number} bikeId
+     * @param {number} userId
+     * @param {"owner" | "rider" | "viewer"} role
+     * @returns {Promise<BikeMember>}
+     */
+    function updateMember(bikeId, userId, role) {
+        return client.put(`/api/bikes/${bikeId}/members/${userId}`, { role });
     }
 
     /**
      * @param {number} bikeId
+     * @param {number} userId
      * @returns {Promise<void>}
      */
-    function deleteBike(bikeId) {
-        return client.delete(`/api/bikes/${bikeId}`);
+    function removeMember(bikeId, userId) {
+        return client.delete(`/api/bikes/${bikeId}/members/${userId}`);
+    }
+
+    /**
+     * @param {number} bikeId
+     * @returns {Promise<RiderTotals[]>}
+     */
+    function getRiders(bikeId) {
+        return client.get(`/api/bikes/${bikeId}/riders`);
     }
 
-    return { getAll, create, delete: deleteBike, get, update };
+    return {
+        getAll,
+        create,
+        delete: deleteBike,
+        get,
+        update,
+        retire,
+        unretire,
+        reorder,
+        getAllCosts,
+        getCosts,
+        getMembers,
+        addMember,
+        updateMember,
+        removeMember,
+        getRiders,
+    };
 }
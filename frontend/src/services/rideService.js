@@ -1,4 +1,5 @@
 import { useApiClient } from "../components/AuthContext.jsx";
+import { ifMatch } from "../lib/ApiClient.js";
 
 /**
  * @typedef RideModel
@@ -10,6 +11,9 @@ import { useApiClient } from "../components/AuthContext.jsx";
  * @property {?string} description
  * @property {?string} deletedAt
  * @property {?number} stravaRide
+ * @property {?number} movingTime
+ * @property {string} updatedAt
+ * @property {number} version
  */
 
 /**
@@ -19,6 +23,7 @@ import { useApiClient } from "../components/AuthContext.jsx";
  * @property {number} distance
  * @property {?string} description
  * @property {?number} stravaRide
+ * @property {?number} movingTime
  */
 
 /**
@@ -89,18 +94,31 @@ export default function useRideService(bikeId) {
     /**
      * @param {number} rideId
      * @param {RidePartial} data
+     * @param {number} version
      * @returns {Promise<RideModel>}
      */
-    function update(rideId, data) {
-        return client.put(`/api/bikes/${bikeId}/rides/${rideId}`, data);
+    function update(rideId, data, version) {
+        return client.put(
+            `/api/bikes/${bikeId}/rides/${rideId}`,
+            data,
+            false,
+            undefined,
+            ifMatch(version)
+        );
     }
 
     /**
      * @param {number} rideId
+     * @param {number} version
      * @returns {Promise<void>}
      */
-    function deleteRide(rideId) {
-        return client.delete(`/api/bikes/${bikeId}/rides/${rideId}`);
+    function deleteRide(rideId, version) {
+        return client.delete(
+            `/api/bikes/${bikeId}/rides/${rideId}`,
+            false,
+            undefined,
+            ifMatch(version)
+        );
     }
 
     /**
@@ -26,10 +26,11 @@ export default function useStravaService() {
     const client = useApiClient();
 
     /**
+     * @param {boolean} importBikes import Strava bikes right after linking
      * @returns {Promise<{ url: string }>}
      */
-    function getOAuthRedirect() {
-        return client.get("/api/strava/link");
+    function getOAuthRedirect(importBikes = false) {
+        return client.get(`/api/strava/link?importBikes=${importBikes}`);
     }
 
     /**
@@ -53,6 +54,15 @@ export default function useStravaService() {
         return client.get("/api/strava/bikes", true);
     }
 
+    /**
+     * Creates bikes for Strava gear not linked to any bike yet.
+     * @param {?string[]} gearIds imports all bikes when null
+     * @returns {Promise<import("./bikeService.js").BikeModel[]>}
+     */
+    function importBikes(gearIds = null) {
+        return client.post("/api/strava/bikes", { gearIds });
+    }
+
     /**
      * @returns {Promise<void>}
      */
@@ -68,5 +78,13 @@ export default function useStravaService() {
         return client.put("/api/strava", data);
     }
 
-    return { getOAuthRedirect, unlink, getLink, getBikes, sync, updateLink };
+    return {
+        getOAuthRedirect,
+        unlink,
+        getLink,
+        getBikes,
+        importBikes,
+        sync,
+        updateLink,
+    };
 }
@@ -73,11 +73,11 @@ class RidesDB {
         await this.#db.delete("rides", mapId(rideId));
     }
 
-    async deleteRide(bikeId, rideId) {
+    async deleteRide(bikeId, rideId, ifMatch) {
         await this.#db.open();
         const ride = await this.#db.get("rides", mapId(rideId));
         if (!ride) {
-            return this.#deleteForeignRide(bikeId, rideId);
+            return this.#deleteForeignRide(bikeId, rideId, ifMatch);
         }
 
         ride.deletedAt = new Date().toISOString();
@@ -85,7 +85,7 @@ class RidesDB {
         return mapId(ride.id);
     }
 
-    async #deleteForeignRide(bikeId, rideId) {
+    async #deleteForeignRide(bikeId, rideId, ifMatch) {
         const ride = {
             id: mapId(rideId),
             date: "",
@@ -93,6 +93,7 @@ class RidesDB {
             description: "",
             deletedAt: new Date().toISOString(),
             bikeId,
+            ifMatch,
         };
         await this.#db.put("rides", ride);
         return rideId;
@@ -12,6 +12,7 @@ async function createRide(request, [bikeId]) {
     const ride = await request.json();
     ride.bikeId = bikeId;
     ride.deletedAt = null;
+    ride.idempotencyKey = crypto.randomUUID();
 
     const id = await ridesDb.addRide(ride);
     ride.id = id;
@@ -65,19 +66,25 @@ async function updateRide(request, [bikeId, rideId]) {
     const ride = await request.json();
     ride.id = rideId;
     ride.bikeId = bikeId;
+    ride.ifMatch = request.headers.get("If-Match");
 
     const updatedId = await ridesDb.updateRide(ride);
     if (updatedId >= 0) {
         console.warn("Updated foreign ride", updatedId);
     }
 
+    delete ride.ifMatch;
     return new Response(JSON.stringify(ride), {
         headers: { "Content-Type": "application/json" },
     });
 }
 
-async function deleteRide(_, [bikeId, rideId]) {
-    const deletedId = await ridesDb.deleteRide(bikeId, rideId);
+async function deleteRide(request, [bikeId, rideId]) {
+    const deletedId = await ridesDb.deleteRide(
+        bikeId,
+        rideId,
+        request.headers.get("If-Match")
+    );
     if (deletedId >= 0) {
         console.warn("Deleted foreign ride", deletedId);
     }
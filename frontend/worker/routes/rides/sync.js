@@ -1,14 +1,21 @@
 import { multiFetch } from "../../lib/fetching.js";
 import ridesDb from "./db.js";
 
+/** Limit of operations accepted by a single bulk request */
+const BULK_LIMIT = 1000;
+
 /**
  * @callback SyncReporter
- * @param {"started" | "failed" | "completed"} type
+ * @param {"started" | "failed" | "conflicted" | "completed"} type
  * @param {string} category
  * @param {number} itemCount
  * @returns {Promise<void>}
  */
 
+/**
+ * @typedef {"synced" | "failed" | "conflicted"} SyncResult
+ */
+
 /**
  * @param {string} token
  * @param {SyncReporter} reportSync
@@ -24,10 +31,35 @@ async function syncRides(token, reportSync) {
     console.log("SYNC", "Syncing rides", rides.length);
     await reportSync("started", "rides", rides.length);
 
-    const tasks = rides.map((r) => syncRide(r, token));
+    /** @type {SyncResult[]} */
+    const results = [];
+    const pending = [];
+    for (const ride of rides) {
+        if (ride.id < 0 && ride.deletedAt) {
+            // Created and deleted while offline, nothing to send
+            await ridesDb.clearRide(ride.id);
+            results.push("synced");
+        } else if (ride.id >= 0 && Number.isNaN(parseVersion(ride.ifMatch))) {
+            // Edits without a known version could overwrite newer changes
+            console.warn("SYNC", "Dropping unversioned edit", ride);
+            await ridesDb.clearRide(ride.id);
+            results.push("conflicted");
+        } else {
+            pending.push(ride);
+        }
+    }
+
+    for (let i = 0; i < pending.length; i += BULK_LIMIT) {
+        const chunk = pending.slice(i, i + BULK_LIMIT);
+        results.push(...(await syncChunk(chunk, token)));
+    }
 
-    const results = await Promise.all(tasks);
-    const failed = results.filter((success) => !success);
+    const failed = results.filter((result) => result === "failed");
+    const conflicted = results.filter((result) => result === "conflicted");
+
+    if (conflicted.length > 0) {
+        await reportSync("conflicted", "rides", conflicted.length);
+    }
 
     if (failed.length > 0) {
         await reportSync("failed", "rides", failed.length);
@@ -38,93 +70,107 @@ async function syncRides(token, reportSync) {
     return true;
 }
 
-async function syncRide(ride, token) {
-    if (ride.id >= 0) {
-        return syncForeignRide(ride, token);
-    }
-
-    if (ride.deletedAt) {
-        await ridesDb.clearRide(ride.id);
-        return true;
-    }
-
-    const body = { ...ride };
-    delete body.id;
-    delete body.bikeId;
-    delete body.deletedAt;
-
-    console.log("SYNC", "Creating new ride", body);
-    const request = new Request(`/api/bikes/${ride.bikeId}/rides`, {
-        method: "POST",
-        headers: {
-            "Content-Type": "application/json",
-            Authorization: `Bearer ${token}`,
-        },
-        body: JSON.stringify(body),
-    });
-    const response = await multiFetch(request);
-
-    if (response.ok) {
-        await ridesDb.clearRide(ride.id);
-        return true;
+/**
+ * Sends the rides in one bulk request. The request is applied all at once,
+ * so a failing ride is set aside and the rest is sent again without it.
+ * @returns {Promise<SyncResult[]>}
+ */
+async function syncChunk(rides, token) {
+    /** @type {SyncResult[]} */
+    const results = [];
+    let remaining = rides;
+    while (remaining.length > 0) {
+        console.log("SYNC", "Sending bulk ride operations", remaining.length);
+        const request = new Request("/api/rides/bulk", {
+            method: "POST",
+            headers: {
+                "Content-Type": "application/json",
+                Authorization: `Bearer ${token}`,
+            },
+            body: JSON.stringify(remaining.map(toOperation)),
+        });
+        const response = await multiFetch(request);
+        const body = await response.json().catch(() => null);
+
+        if (!body?.results) {
+            console.error("SYNC", "Failed to send rides", response);
+            return [...results, ...remaining.map(() => "failed")];
+        }
+
+        if (body.committed) {
+            const cleared = remaining.map((ride) => ridesDb.clearRide(ride.id));
+            await Promise.all(cleared);
+            return [...results, ...remaining.map(() => "synced")];
+        }
+
+        const index = body.results.findIndex(
+            (result) => result.status === "error"
+        );
+        if (index === -1) {
+            return [...results, ...remaining.map(() => "failed")];
+        }
+
+        const ride = remaining[index];
+        const error = body.results[index];
+        results.push(await handleFailure(ride, error));
+        remaining = remaining.filter((_, i) => i !== index);
     }
 
-    console.error("SYNC: Failed to create ride", response);
-    return false;
+    return results;
 }
 
-async function syncForeignRide(ride, token) {
-    console.log("SYNC", "Syncing foreign ride", ride);
-    if (ride.deletedAt) {
-        return syncDeleteForeignRide(ride, token);
+/**
+ * Edits of outdated or missing rides can never succeed, they are dropped
+ * in favor of the server copy instead of being resent on every sync.
+ * @returns {Promise<SyncResult>}
+ */
+async function handleFailure(ride, error) {
+    if (ride.id >= 0 && (error.code === 409 || error.code === 404)) {
+        console.warn("SYNC", "Dropping conflicting edit", ride, error.message);
+        await ridesDb.clearRide(ride.id);
+        return "conflicted";
     }
 
-    return syncUpdateForeignRide(ride, token);
+    console.error("SYNC", "Failed to sync ride", ride, error.message);
+    return "failed";
 }
 
-async function syncUpdateForeignRide(ride, token) {
+function toOperation(ride) {
     const body = { ...ride };
     delete body.id;
     delete body.bikeId;
     delete body.deletedAt;
+    delete body.idempotencyKey;
+    delete body.ifMatch;
+
+    if (ride.id < 0) {
+        // Retried creations return the ride created by the first attempt
+        return {
+            op: "create",
+            bikeId: ride.bikeId,
+            ride: body,
+            idempotencyKey: ride.idempotencyKey,
+        };
+    }
 
-    console.log("SYNC", "Updating existing ride", body);
-    const request = new Request(`/api/bikes/${ride.bikeId}/rides/${ride.id}`, {
-        method: "PUT",
-        headers: {
-            "Content-Type": "application/json",
-            Authorization: `Bearer ${token}`,
-        },
-        body: JSON.stringify(body),
-    });
-    const response = await multiFetch(request);
-
-    if (response.ok) {
-        await ridesDb.clearRide(ride.id);
-        return true;
+    const version = parseVersion(ride.ifMatch);
+    if (ride.deletedAt) {
+        return { op: "delete", rideId: ride.id, version };
     }
 
-    console.error("SYNC", "Failed to update ride", response);
-    return false;
+    return { op: "update", rideId: ride.id, ride: body, version };
 }
 
-async function syncDeleteForeignRide(ride, token) {
-    console.log("SYNC", "Deleting existing ride", ride);
-    const request = new Request(`/api/bikes/${ride.bikeId}/rides/${ride.id}`, {
-        method: "DELETE",
-        headers: {
-            Authorization: `Bearer ${token}`,
-        },
-    });
-    const response = await multiFetch(request);
-
-    if (response.ok) {
-        await ridesDb.clearRide(ride.id);
-        return true;
+/**
+ * @param {?string} ifMatch `If-Match` header captured with the edit
+ * @returns {number} `NaN` when the edit does not name a version
+ */
+function parseVersion(ifMatch) {
+    if (!ifMatch || ifMatch.trim() === "*") {
+        return NaN;
     }
 
-    console.error("SYNC", "Failed to delete ride", response);
-    return false;
+    return Number(ifMatch.trim().replace(/^W\//, "").replaceAll('"', ""));
 }
 
 export default syncRides;